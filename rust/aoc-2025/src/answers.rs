@@ -0,0 +1,155 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::path::Path;
+
+use toml::Spanned;
+
+use crate::error::AocError;
+use crate::shared::{self, Part};
+
+pub const ANSWERS_FILE: &str = "answers.toml";
+
+/// Known-correct answers, read from a file shaped like
+///
+/// ```toml
+/// [day1]
+/// part1 = 1023
+/// part2 = "5899"
+/// ```
+pub struct Answers {
+  expected: HashMap<(u8, Part), String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CheckOutcome {
+  Pass,
+  Fail { expected: String },
+  Missing,
+}
+
+fn line_and_column(text: &str, offset: usize) -> (usize, usize) {
+  let before = &text[..offset.min(text.len())];
+  let line = before.matches('\n').count() + 1;
+  let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+  (line, column)
+}
+
+/// The answers for one day, keyed by part, with where each came from.
+type Parts = BTreeMap<Spanned<String>, Spanned<toml::Value>>;
+
+fn parse_error(text: &str, span: Range<usize>, message: impl Into<String>) -> AocError {
+  let (line, column) = line_and_column(text, span.start);
+  AocError::parse(line, column, message)
+}
+
+fn key_error(text: &str, key: &Spanned<String>, message: &str) -> AocError {
+  parse_error(
+    text,
+    key.span(),
+    format!("Invalid answers entry '{}': {}", key.get_ref(), message),
+  )
+}
+
+fn parse_day_key(text: &str, key: &Spanned<String>) -> Result<u8, AocError> {
+  key
+    .get_ref()
+    .strip_prefix("day")
+    .and_then(|n| n.parse::<u8>().ok())
+    .ok_or_else(|| key_error(text, key, "expected a table named like [day3]"))
+}
+
+fn parse_part_key(text: &str, key: &Spanned<String>) -> Result<Part, AocError> {
+  key
+    .get_ref()
+    .strip_prefix("part")
+    .and_then(|n| n.parse::<Part>().ok())
+    .ok_or_else(|| key_error(text, key, "expected part1 or part2"))
+}
+
+impl Answers {
+  pub fn parse(text: &str) -> Result<Answers, AocError> {
+    let table = toml::from_str::<BTreeMap<Spanned<String>, Spanned<Parts>>>(text)
+      .map_err(|e| parse_error(text, e.span().unwrap_or(0..0), e.message()))?;
+
+    let mut expected = HashMap::new();
+    for (day_key, parts) in &table {
+      let day = parse_day_key(text, day_key)?;
+      for (part_key, answer) in parts.get_ref() {
+        let part = parse_part_key(text, part_key)?;
+        let answer = match answer.get_ref() {
+          toml::Value::String(s) => s.clone(),
+          toml::Value::Integer(n) => n.to_string(),
+          _ => {
+            return Err(parse_error(
+              text,
+              answer.span(),
+              format!(
+                "Invalid answers entry '{}': expected a string or integer answer",
+                part_key.get_ref()
+              ),
+            ));
+          }
+        };
+        expected.insert((day, part), answer);
+      }
+    }
+
+    Ok(Answers { expected })
+  }
+
+  pub fn load(path: &Path) -> Result<Answers, AocError> {
+    Answers::parse(&shared::load_file_str(path)?)
+  }
+
+  pub fn check(&self, day: u8, part: Part, actual: &str) -> CheckOutcome {
+    match self.expected.get(&(day, part)) {
+      None => CheckOutcome::Missing,
+      Some(expected) if expected == actual => CheckOutcome::Pass,
+      Some(expected) => CheckOutcome::Fail {
+        expected: expected.clone(),
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_check() {
+    let answers =
+      Answers::parse("[day1]\npart1 = 3\npart2 = \"6\"\n\n[day8]\npart2 = 25272\n").unwrap();
+    assert_eq!(answers.check(1, Part::One, "3"), CheckOutcome::Pass);
+    assert_eq!(answers.check(1, Part::Two, "6"), CheckOutcome::Pass);
+    assert_eq!(
+      answers.check(8, Part::Two, "1"),
+      CheckOutcome::Fail {
+        expected: "25272".to_string()
+      }
+    );
+    assert_eq!(answers.check(8, Part::One, "40"), CheckOutcome::Missing);
+  }
+
+  #[test]
+  fn test_invalid_answers() {
+    assert!(matches!(
+      Answers::parse("[day1]\npart1 = = 3\n"),
+      Err(AocError::Parse { line: 2, .. })
+    ));
+    let position = |text: &str| match Answers::parse(text) {
+      Err(AocError::Parse { line, column, .. }) => Some((line, column)),
+      _ => None,
+    };
+    assert_eq!(position("[dayX]\npart1 = 3\n"), Some((1, 2)));
+    assert_eq!(position("[day1]\npart1 = 3\npart3 = 3\n"), Some((3, 1)));
+    assert_eq!(position("[day1]\n  part1 = 1.5\n"), Some((2, 11)));
+    assert_eq!(position("day1 = 3\n"), Some((1, 8)));
+
+    let error = Answers::parse("[day2]\npart0 = 3\n").err().unwrap();
+    assert_eq!(
+      error.to_string(),
+      "line 2, column 1: Invalid answers entry 'part0': expected part1 or part2"
+    );
+  }
+}
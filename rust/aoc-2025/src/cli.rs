@@ -0,0 +1,224 @@
+use std::path::PathBuf;
+
+use crate::day6::Precision;
+use crate::output::Format;
+use crate::shared::Part;
+
+pub const USAGE: &str = "\
+Usage: aoc-2025 [OPTIONS]
+
+Options:
+  -d, --day <DAYS>       Days to run, e.g. `3`, `3,5-8` (default: all days)
+  -p, --part <PART>      Only run part 1 or part 2 (default: both)
+  -i, --input <FILE>     Read the puzzle input from FILE, or from stdin if FILE is `-`
+                         (default: dayN.txt in the input directory; requires a single day)
+      --input-dir <DIR>  Directory holding dayN.txt (default: $AOC_INPUT_DIR, or the
+                         nearest puzzleInput directory above the crate)
+  -c, --check            Compare each answer with the known answers and exit non-zero on
+                         a mismatch
+      --answers <FILE>   Known answers for --check (default: answers.toml in the input
+                         directory)
+  -t, --time             Show how long parsing and each part took
+      --bench <N>        Run every stage N times and print a tab-separated table of the
+                         min, median and p95 durations
+  -f, --format <FORMAT>  Output format: text, json or ndjson (default: text). JSON output
+                         ends with a summary record holding --bench and --check results
+      --precision <TYPE> Integer type day 6 computes in: i64 or i128 (default: i64)
+  -h, --help             Print this help";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputSource {
+  Default,
+  File(PathBuf),
+  Stdin,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Args {
+  pub days: Option<Vec<u8>>,
+  pub parts: Vec<Part>,
+  pub input: InputSource,
+  pub input_dir: Option<PathBuf>,
+  pub check: bool,
+  pub answers: Option<PathBuf>,
+  pub time: bool,
+  pub bench: Option<usize>,
+  pub format: Format,
+  pub precision: Precision,
+  pub help: bool,
+}
+
+fn parse_days(spec: &str) -> Result<Vec<u8>, String> {
+  let mut days = vec![];
+  for token in spec.split(',').map(str::trim) {
+    let parse_day = |s: &str| {
+      s.trim()
+        .parse::<u8>()
+        .map_err(|e| format!("Invalid day '{}' in '{}': {}", s, spec, e))
+    };
+
+    match token.split_once('-') {
+      Some((start, end)) => {
+        let (start, end) = (parse_day(start)?, parse_day(end)?);
+        if start > end {
+          return Err(format!("Invalid day range '{}': start is after end", token));
+        }
+        days.extend(start..=end);
+      }
+      None => days.push(parse_day(token)?),
+    }
+  }
+
+  days.sort();
+  days.dedup();
+  Ok(days)
+}
+
+fn parse_part(spec: &str) -> Result<Part, String> {
+  spec
+    .trim()
+    .parse::<Part>()
+    .map_err(|_| format!("Invalid part '{}': expected 1 or 2", spec))
+}
+
+fn parse_iterations(spec: &str) -> Result<usize, String> {
+  match spec.trim().parse::<usize>() {
+    Ok(n) if n > 0 => Ok(n),
+    _ => Err(format!(
+      "Invalid iteration count '{}': expected a positive integer",
+      spec
+    )),
+  }
+}
+
+pub fn parse_args<I>(args: I) -> Result<Args, String>
+where
+  I: IntoIterator<Item = String>,
+{
+  let mut parsed = Args {
+    days: None,
+    parts: Part::ALL.to_vec(),
+    input: InputSource::Default,
+    input_dir: None,
+    check: false,
+    answers: None,
+    time: false,
+    bench: None,
+    format: Format::Text,
+    precision: Precision::I64,
+    help: false,
+  };
+
+  let mut args = args.into_iter();
+  while let Some(arg) = args.next() {
+    let (flag, inline_value) = match arg.split_once('=') {
+      Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+      _ => (arg.clone(), None),
+    };
+
+    let mut value = || {
+      inline_value
+        .clone()
+        .or_else(|| args.next())
+        .ok_or(format!("Missing value for '{}'", flag))
+    };
+
+    match flag.as_str() {
+      "-d" | "--day" => parsed.days = Some(parse_days(&value()?)?),
+      "-p" | "--part" => parsed.parts = vec![parse_part(&value()?)?],
+      "-i" | "--input" => {
+        parsed.input = match value()?.as_str() {
+          "-" => InputSource::Stdin,
+          path => InputSource::File(PathBuf::from(path)),
+        }
+      }
+      "--input-dir" => parsed.input_dir = Some(PathBuf::from(value()?)),
+      "-c" | "--check" => parsed.check = true,
+      "--answers" => parsed.answers = Some(PathBuf::from(value()?)),
+      "-t" | "--time" => parsed.time = true,
+      "--bench" => parsed.bench = Some(parse_iterations(&value()?)?),
+      "-f" | "--format" => {
+        let format = value()?;
+        parsed.format = format
+          .trim()
+          .parse::<Format>()
+          .map_err(|_| format!("Invalid format '{}': expected text, json or ndjson", format))?
+      }
+      "--precision" => {
+        let precision = value()?;
+        parsed.precision = precision
+          .trim()
+          .parse::<Precision>()
+          .map_err(|_| format!("Invalid precision '{}': expected i64 or i128", precision))?
+      }
+      "-h" | "--help" => parsed.help = true,
+      _ => return Err(format!("Unknown argument '{}'", arg)),
+    }
+  }
+
+  if parsed.input != InputSource::Default && parsed.days.as_ref().is_none_or(|d| d.len() != 1) {
+    return Err("--input requires exactly one --day".to_string());
+  }
+
+  Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn args(raw: &[&str]) -> Result<Args, String> {
+    parse_args(raw.iter().map(|s| s.to_string()))
+  }
+
+  #[test]
+  fn test_parse_days() {
+    assert_eq!(parse_days("3").unwrap(), vec![3]);
+    assert_eq!(parse_days("3,5-8").unwrap(), vec![3, 5, 6, 7, 8]);
+    assert_eq!(parse_days("8, 1-2, 2").unwrap(), vec![1, 2, 8]);
+    assert!(parse_days("5-3").is_err());
+    assert!(parse_days("x").is_err());
+  }
+
+  #[test]
+  fn test_parse_args() {
+    let parsed = args(&[]).unwrap();
+    assert_eq!(parsed.days, None);
+    assert_eq!(parsed.parts, vec![Part::One, Part::Two]);
+    assert_eq!(parsed.input, InputSource::Default);
+
+    let parsed = args(&["--day", "4", "-p", "2", "--input=sample.txt"]).unwrap();
+    assert_eq!(parsed.days, Some(vec![4]));
+    assert_eq!(parsed.parts, vec![Part::Two]);
+    assert_eq!(parsed.input, InputSource::File(PathBuf::from("sample.txt")));
+
+    let parsed = args(&["-d", "7", "-i", "-"]).unwrap();
+    assert_eq!(parsed.input, InputSource::Stdin);
+
+    let parsed = args(&["--input-dir", "../inputs"]).unwrap();
+    assert_eq!(parsed.input_dir, Some(PathBuf::from("../inputs")));
+
+    let parsed = args(&["--check", "--answers", "known.toml"]).unwrap();
+    assert!(parsed.check);
+    assert_eq!(parsed.answers, Some(PathBuf::from("known.toml")));
+
+    let parsed = args(&["-t", "--bench", "20"]).unwrap();
+    assert!(parsed.time);
+    assert_eq!(parsed.bench, Some(20));
+    assert!(args(&["--bench", "0"]).is_err());
+
+    let parsed = args(&["--format", "ndjson"]).unwrap();
+    assert_eq!(parsed.format, Format::Ndjson);
+    assert!(args(&["--format", "xml"]).is_err());
+
+    assert_eq!(args(&[]).unwrap().precision, Precision::I64);
+    let parsed = args(&["--precision=i128"]).unwrap();
+    assert_eq!(parsed.precision, Precision::I128);
+    assert!(args(&["--precision", "i32"]).is_err());
+
+    assert!(args(&["--part", "3"]).is_err());
+    assert!(args(&["--day"]).is_err());
+    assert!(args(&["--bogus"]).is_err());
+    assert!(args(&["--day", "1-2", "--input", "x.txt"]).is_err());
+  }
+}
@@ -1,11 +1,10 @@
 use std::cmp::max;
-use std::io;
-use std::str::FromStr;
 
-use crate::shared::load_input_lines;
+use crate::error::AocError;
+use crate::solution::Solution;
 
 #[derive(Debug)]
-enum Input {
+pub enum Input {
   Left(i16),
   Right(i16),
 }
@@ -19,11 +18,22 @@ impl std::fmt::Display for Input {
   }
 }
 
-fn parse_input(line: &str) -> Result<Input, <i16 as FromStr>::Err> {
-  if line.chars().next() == Some('L') {
-    Ok(Input::Left(line[1..].parse()?))
+fn parse_input(line_no: usize, line: &str) -> Result<Input, AocError> {
+  let parse_distance = |n: &str| {
+    n.parse::<i16>()
+      .map_err(|e| AocError::parse(line_no, 2, format!("Invalid distance '{}': {}", n, e)))
+  };
+
+  if let Some(n) = line.strip_prefix('L') {
+    Ok(Input::Left(parse_distance(n)?))
+  } else if let Some(n) = line.strip_prefix('R') {
+    Ok(Input::Right(parse_distance(n)?))
   } else {
-    Ok(Input::Right(line[1..].parse()?))
+    Err(AocError::parse(
+      line_no,
+      1,
+      format!("Expected a rotation like 'L5' or 'R12', got '{}'", line),
+    ))
   }
 }
 
@@ -35,7 +45,7 @@ fn positive_mod(a: i16, b: i16) -> i16 {
   }
 }
 
-fn solve_puzzle1(inputs: &Vec<Input>) -> usize {
+fn solve_puzzle1(inputs: &[Input]) -> usize {
   let starting_pos = 50;
   let ending_spots = inputs.iter().scan(starting_pos, |state, element| {
     *state = match element {
@@ -49,7 +59,7 @@ fn solve_puzzle1(inputs: &Vec<Input>) -> usize {
   ending_spots.filter(|x| *x == 0).count()
 }
 
-fn solve_puzzle2(inputs: &Vec<Input>) -> i16 {
+fn solve_puzzle2(inputs: &[Input]) -> i16 {
   let starting_pos = 50;
 
   let zero_traversals = inputs.iter().scan(starting_pos, |state, element| {
@@ -69,21 +79,31 @@ fn solve_puzzle2(inputs: &Vec<Input>) -> i16 {
   zero_traversals.sum()
 }
 
-pub fn run() -> io::Result<()> {
-  let input_lines = load_input_lines("day1.txt")?;
-  let inputs = input_lines
-    .iter()
-    .map(|line| parse_input(line))
-    .collect::<Result<Vec<_>, _>>()
-    .expect("Unable to parse input file");
+pub struct Day1;
+
+impl Solution for Day1 {
+  type Input = Vec<Input>;
+  type Output1 = usize;
+  type Output2 = i16;
 
-  println!("Day 1 Puzzle 1: {}", solve_puzzle1(&inputs));
-  println!("Day 1 Puzzle 2: {}", solve_puzzle2(&inputs));
+  fn parse(input: &str) -> Result<Self::Input, AocError> {
+    (1..)
+      .zip(input.lines())
+      .map(|(line_no, line)| parse_input(line_no, line))
+      .collect()
+  }
+
+  fn part1(input: &Self::Input) -> Result<Self::Output1, AocError> {
+    Ok(solve_puzzle1(input))
+  }
 
-  Ok(())
+  fn part2(input: &Self::Input) -> Result<Self::Output2, AocError> {
+    Ok(solve_puzzle2(input))
+  }
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests {
   use super::*;
 
@@ -110,10 +130,30 @@ mod tests {
 
     let parsed_inputs = given_inputs
       .iter()
-      .map(|line| parse_input(line).unwrap())
+      .map(|line| parse_input(1, line).unwrap())
       .collect::<Vec<_>>();
 
     assert_eq!(solve_puzzle1(&parsed_inputs), 3);
     assert_eq!(solve_puzzle2(&parsed_inputs), 6);
   }
+
+  #[test]
+  fn parse_errors_carry_position() {
+    assert!(matches!(
+      parse_input(4, "X12"),
+      Err(AocError::Parse {
+        line: 4,
+        column: 1,
+        ..
+      })
+    ));
+    assert!(matches!(
+      parse_input(2, "Lx"),
+      Err(AocError::Parse {
+        line: 2,
+        column: 2,
+        ..
+      })
+    ));
+  }
 }
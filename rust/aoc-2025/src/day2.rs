@@ -1,80 +1,356 @@
-use crate::shared::load_input_str;
-
-fn parse_input(input: &str) -> Vec<(i64, i64)> {
-  let pairs = input.split(",");
-  pairs
-    .map(|pair| {
-      let mut nums = pair.trim().split("-");
-      let first = nums
-        .next()
-        .expect(format!("Unable to parse pair '{}' with the pattern '_-_'.", pair).as_str())
-        .parse::<i64>()
-        .expect(format!("Unable to parse '{}' as a pair of numbers.", pair).as_str());
-      let second = nums
-        .next()
-        .expect(format!("Unable to parse pair '{}' with the pattern '_-_'.", pair).as_str())
-        .parse::<i64>()
-        .expect(format!("Unable to parse '{}' as a pair of numbers.", pair).as_str());
-
-      (first, second)
+use std::iter::Peekable;
+use std::ops::{Add, RangeInclusive, Sub};
+
+use crate::error::AocError;
+use crate::shared::range_set::parse_range;
+use crate::solution::Solution;
+
+fn parse_input(input: &str) -> Result<Vec<(i64, i64)>, AocError> {
+  (1..)
+    .zip(input.lines())
+    .flat_map(|(line_no, line)| {
+      line
+        .split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(move |pair| parse_range(line_no, line, pair))
     })
     .collect()
 }
 
-mod puzzle1 {
+/// How many times a block must be written out for an ID to match. An ID can
+/// match several counts at once, e.g. 111111 is 2 × `111` and 3 × `11`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Repetitions {
+  Exactly(u32),
+  AtLeast(u32),
+  AnyOf(Vec<u32>),
+}
 
-  fn is_repeat_pair(i: i64) -> bool {
-    let s = i.to_string();
-    let chars: Vec<char> = s.chars().collect();
-    let len = chars.len();
-    if len % 2 == 1 {
-      return false;
+impl Repetitions {
+  pub fn allows(&self, repeats: u32) -> bool {
+    match self {
+      Repetitions::Exactly(k) => repeats == *k,
+      Repetitions::AtLeast(k) => repeats >= *k,
+      Repetitions::AnyOf(ks) => ks.contains(&repeats),
     }
-    let forwards = chars[0..len / 2].to_vec();
-    let backwards = chars[(len + 1) / 2..].iter();
-    forwards.iter().zip(backwards).all(|(a, b)| a == b)
   }
+}
 
-  pub fn invalid_ids(l: i64, r: i64) -> Vec<i64> {
-    (l..=r).filter(|i| is_repeat_pair(*i)).collect()
+/// The base IDs are written in, which is at least 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Radix(u32);
+
+impl Radix {
+  pub const DECIMAL: Radix = Radix(10);
+
+  /// `None` for 0 and 1, which can't write every ID.
+  pub const fn new(radix: u32) -> Option<Radix> {
+    if radix >= 2 { Some(Radix(radix)) } else { None }
   }
 
-  pub fn solve(input: &Vec<(i64, i64)>) -> i64 {
-    input.iter().flat_map(|(a, b)| invalid_ids(*a, *b)).sum()
+  pub const fn get(self) -> u32 {
+    self.0
   }
 }
 
-mod puzzle2 {
-  pub fn is_repeat_any_size(i: i64) -> bool {
-    let s = i.to_string();
-    let chars: Vec<char> = s.chars().collect();
-    let len = chars.len();
-    let mut chunk_sizes = 1..=(len / 2);
-    chunk_sizes.any(|size| {
-      let mut chunks = chars.chunks(size);
-      match chunks.next() {
-        None => false,
-        Some(first_window) => chunks.all(|w| w == first_window),
-      }
+/// IDs found across a set of ranges. An ID is found once per range that
+/// contains it, so overlapping ranges count it more than once.
+#[derive(Debug, Clone, Copy)]
+pub struct RepeatedIds<'a> {
+  ranges: &'a [(i64, i64)],
+  repetitions: &'a Repetitions,
+  radix: Radix,
+}
+
+impl<'a> RepeatedIds<'a> {
+  /// The IDs range by range, each range's in ascending order.
+  pub fn ids(&self) -> impl Iterator<Item = i64> + 'a {
+    let (repetitions, radix) = (self.repetitions, self.radix);
+    self
+      .ranges
+      .iter()
+      .flat_map(move |(l, r)| repeated_ids(*l, *r, repetitions, radix))
+  }
+
+  /// The number of IDs and their sum, worked out without listing them, or
+  /// `None` if the sum overflows.
+  pub fn tally(&self) -> Option<Tally> {
+    self
+      .ranges
+      .iter()
+      .map(|(l, r)| tally_repeated_ids(*l, *r, self.repetitions, self.radix))
+      .try_fold(Tally::default(), Tally::checked_add)
+  }
+}
+
+/// How many IDs were found, and their sum.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+  pub count: u64,
+  pub sum: i128,
+}
+
+impl Tally {
+  pub fn checked_add(self, other: Tally) -> Option<Tally> {
+    Some(Tally {
+      count: self.count.checked_add(other.count)?,
+      sum: self.sum.checked_add(other.sum)?,
     })
   }
+}
+
+impl Add for Tally {
+  type Output = Tally;
+
+  fn add(self, other: Tally) -> Tally {
+    Tally {
+      count: self.count + other.count,
+      sum: self.sum + other.sum,
+    }
+  }
+}
+
+impl Sub for Tally {
+  type Output = Tally;
+
+  fn sub(self, other: Tally) -> Tally {
+    Tally {
+      count: self.count - other.count,
+      sum: self.sum - other.sum,
+    }
+  }
+}
+
+/// The most digits an `i64` can have when written in `radix`.
+fn max_digits(radix: u32) -> u32 {
+  i64::MAX.ilog(radix as i64) + 1
+}
+
+/// Whether `id`, written in `radix`, is a single block repeated a number of
+/// times that `repetitions` allows.
+pub fn is_repeated(id: i64, repetitions: &Repetitions, radix: Radix) -> bool {
+  let radix = radix.get();
+  if id <= 0 {
+    return false;
+  }
+
+  let mut digits = vec![];
+  let mut rest = id;
+  while rest > 0 {
+    digits.push(rest % radix as i64);
+    rest /= radix as i64;
+  }
+
+  (1..=digits.len() as u32).any(|repeats| {
+    let len = digits.len();
+    let block_len = len / repeats as usize;
+    repetitions.allows(repeats)
+      && len.is_multiple_of(repeats as usize)
+      && digits
+        .chunks(block_len)
+        .all(|chunk| chunk == &digits[..block_len])
+  })
+}
+
+/// Multiplying a `block_len`-digit block by this writes the block out
+/// `repeats` times, e.g. `12 * repeat_multiplier(2, 3, 10) == 12 * 10101 == 121212`.
+fn repeat_multiplier(block_len: u32, repeats: u32, radix: u32) -> i128 {
+  (0..repeats)
+    .map(|i| (radix as i128).pow(block_len * i))
+    .sum()
+}
+
+/// The blocks of `block_len` digits (without leading zeros) that land in
+/// `l..=r` when written `repeats` times, and the multiplier that does so.
+fn blocks_of_shape(
+  l: i64,
+  r: i64,
+  block_len: u32,
+  repeats: u32,
+  radix: u32,
+) -> (RangeInclusive<i128>, i128) {
+  let multiplier = repeat_multiplier(block_len, repeats, radix);
+  let min_block = (radix as i128).pow(block_len - 1);
+  let max_block = (radix as i128).pow(block_len) - 1;
+
+  let first = ((l.max(0) as i128 + multiplier - 1) / multiplier).max(min_block);
+  let last = (r as i128).div_euclid(multiplier).min(max_block);
+  (first..=last, multiplier)
+}
+
+/// Every ID in `l..=r` made of a `block_len`-digit block written `repeats`
+/// times, in ascending order. Only the blocks whose repetition lands inside
+/// the range are visited.
+fn repeated_ids_of_shape(
+  l: i64,
+  r: i64,
+  block_len: u32,
+  repeats: u32,
+  radix: u32,
+) -> impl Iterator<Item = i64> {
+  let (blocks, multiplier) = blocks_of_shape(l, r, block_len, repeats, radix);
+  blocks.map(move |block| (block * multiplier) as i64)
+}
+
+/// The IDs `repeated_ids_of_shape` would list, counted and summed as an
+/// arithmetic series of blocks.
+fn tally_of_shape(l: i64, r: i64, block_len: u32, repeats: u32, radix: u32) -> Tally {
+  let (blocks, multiplier) = blocks_of_shape(l, r, block_len, repeats, radix);
+  let (first, last) = (*blocks.start(), *blocks.end());
+  if first > last {
+    return Tally::default();
+  }
+
+  // Halve whichever factor is even, as the sum can come within a factor of
+  // two of `i128::MAX`.
+  let count = last - first + 1;
+  let sum = if count % 2 == 0 {
+    count / 2 * (first + last)
+  } else {
+    count * ((first + last) / 2)
+  };
+  Tally {
+    count: count as u64,
+    sum: sum * multiplier,
+  }
+}
+
+/// Counts and sums the `digits`-digit IDs in `l..=r` that `is_repeated`
+/// accepts.
+///
+/// An ID whose shortest block has `b` digits can be written with any block
+/// whose length is a multiple of `b` and divides `digits`, and with no other.
+/// So each ID is counted once, under its shortest block, by working out how
+/// many IDs each block length makes that no shorter block also makes.
+fn tally_of_length(l: i64, r: i64, digits: u32, repetitions: &Repetitions, radix: u32) -> Tally {
+  let block_lens = (1..=digits)
+    .filter(|len| digits.is_multiple_of(*len))
+    .collect::<Vec<_>>();
+  let mut shortest = Vec::<Tally>::with_capacity(block_lens.len());
+  let mut total = Tally::default();
+  for (i, &block_len) in block_lens.iter().enumerate() {
+    let shorter = (0..i)
+      .filter(|j| block_len.is_multiple_of(block_lens[*j]))
+      .fold(Tally::default(), |tally, j| tally + shortest[j]);
+    let tally = tally_of_shape(l, r, block_len, digits / block_len, radix) - shorter;
+    shortest.push(tally);
+
+    let allowed = block_lens[i..]
+      .iter()
+      .any(|len| len.is_multiple_of(block_len) && repetitions.allows(digits / len));
+    if allowed {
+      total = total + tally;
+    }
+  }
+  total
+}
+
+/// Counts and sums the IDs that `repeated_ids` lists, without listing them,
+/// so the cost doesn't depend on the number of hits either.
+pub fn tally_repeated_ids(l: i64, r: i64, repetitions: &Repetitions, radix: Radix) -> Tally {
+  let radix = radix.get();
+  (1..=max_digits(radix))
+    .map(|digits| tally_of_length(l, r, digits, repetitions, radix))
+    .fold(Tally::default(), Tally::add)
+}
+
+/// Merges strictly ascending iterators into one, yielding values that
+/// several of them share only once.
+fn merge_unique(mut shapes: Vec<Peekable<impl Iterator<Item = i64>>>) -> impl Iterator<Item = i64> {
+  std::iter::from_fn(move || {
+    let next = shapes
+      .iter_mut()
+      .filter_map(|ids| ids.peek().copied())
+      .min()?;
+    for ids in &mut shapes {
+      ids.next_if_eq(&next);
+    }
+    Some(next)
+  })
+}
+
+/// The IDs in `l..=r` that `is_repeated` accepts, in ascending order, built
+/// lazily from their blocks so the cost depends on the number of hits
+/// rather than the width of the range.
+pub fn repeated_ids(
+  l: i64,
+  r: i64,
+  repetitions: &Repetitions,
+  radix: Radix,
+) -> impl Iterator<Item = i64> + '_ {
+  let radix = radix.get();
+  // IDs with fewer digits are smaller, so only the shapes for one digit
+  // count need merging at a time. An ID like 111111 can be built from
+  // blocks of 1, 2 or 3 digits, so the merge removes the duplicates.
+  (1..=max_digits(radix)).flat_map(move |digits| {
+    let shapes = (1..=digits)
+      .filter(|repeats| digits.is_multiple_of(*repeats) && repetitions.allows(*repeats))
+      .map(|repeats| repeated_ids_of_shape(l, r, digits / repeats, repeats, radix).peekable())
+      .collect();
+    merge_unique(shapes)
+  })
+}
 
+pub fn find_repeated_ids<'a>(
+  ranges: &'a [(i64, i64)],
+  repetitions: &'a Repetitions,
+  radix: Radix,
+) -> RepeatedIds<'a> {
+  RepeatedIds {
+    ranges,
+    repetitions,
+    radix,
+  }
+}
+
+mod puzzle1 {
+  use super::{Radix, Repetitions, find_repeated_ids};
+
+  #[cfg(test)]
   pub fn invalid_ids(l: i64, r: i64) -> Vec<i64> {
-    (l..=r).filter(|i| is_repeat_any_size(*i)).collect()
+    super::repeated_ids(l, r, &Repetitions::Exactly(2), Radix::DECIMAL).collect()
   }
 
-  pub fn solve(input: &Vec<(i64, i64)>) -> i64 {
-    input.iter().flat_map(|(a, b)| invalid_ids(*a, *b)).sum()
+  /// The sum of the invalid IDs, or `None` if it overflows.
+  pub fn solve(input: &[(i64, i64)]) -> Option<i128> {
+    let found = find_repeated_ids(input, &Repetitions::Exactly(2), Radix::DECIMAL);
+    found.tally().map(|tally| tally.sum)
   }
 }
 
-pub fn run() {
-  let input = load_input_str("day2.txt").unwrap();
-  let parsed_input = parse_input(&input);
-  let puz1 = puzzle1::solve(&parsed_input);
-  let puz2 = puzzle2::solve(&parsed_input);
-  println!("Day 2 Puzzle 1: {}", puz1);
-  println!("Day 2 Puzzle 2: {}", puz2);
+mod puzzle2 {
+  use super::{Radix, Repetitions, find_repeated_ids};
+
+  #[cfg(test)]
+  pub fn invalid_ids(l: i64, r: i64) -> Vec<i64> {
+    super::repeated_ids(l, r, &Repetitions::AtLeast(2), Radix::DECIMAL).collect()
+  }
+
+  /// The sum of the invalid IDs, or `None` if it overflows.
+  pub fn solve(input: &[(i64, i64)]) -> Option<i128> {
+    let found = find_repeated_ids(input, &Repetitions::AtLeast(2), Radix::DECIMAL);
+    found.tally().map(|tally| tally.sum)
+  }
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+  type Input = Vec<(i64, i64)>;
+  type Output1 = i128;
+  type Output2 = i128;
+
+  fn parse(input: &str) -> Result<Self::Input, AocError> {
+    parse_input(input)
+  }
+
+  fn part1(input: &Self::Input) -> Result<Self::Output1, AocError> {
+    puzzle1::solve(input).ok_or_else(|| AocError::solve("Sum of invalid IDs overflows"))
+  }
+
+  fn part2(input: &Self::Input) -> Result<Self::Output2, AocError> {
+    puzzle2::solve(input).ok_or_else(|| AocError::solve("Sum of invalid IDs overflows"))
+  }
 }
 
 #[cfg(test)]
@@ -94,9 +370,9 @@ mod tests {
   #[test]
   fn solve_given_input_for_puzzle_1() {
     let input = "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,2121212118-2121212124";
-    let parsed_input = parse_input(input);
+    let parsed_input = parse_input(input).unwrap();
     let result = puzzle1::solve(&parsed_input);
-    assert_eq!(result, 1227775554);
+    assert_eq!(result, Some(1227775554));
   }
 
   #[test]
@@ -112,8 +388,225 @@ mod tests {
   #[test]
   fn solve_given_input_for_puzzle_2() {
     let input = "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,2121212118-2121212124";
-    let parsed_input = parse_input(input);
+    let parsed_input = parse_input(input).unwrap();
     let result = puzzle2::solve(&parsed_input);
-    assert_eq!(result, 4174379265);
+    assert_eq!(result, Some(4174379265));
+  }
+
+  fn is_repeat_pair(i: i64) -> bool {
+    let s = i.to_string();
+    let len = s.len();
+    len.is_multiple_of(2) && s[..len / 2] == s[len / 2..]
+  }
+
+  fn is_repeat_any_size(i: i64) -> bool {
+    let chars = i.to_string().chars().collect::<Vec<char>>();
+    (1..=chars.len() / 2).any(|size| {
+      let mut chunks = chars.chunks(size);
+      let first = chunks.next();
+      chars.len().is_multiple_of(size) && chunks.all(|chunk| Some(chunk) == first)
+    })
+  }
+
+  #[test]
+  fn generated_ids_match_brute_force() {
+    for (l, r) in [(0, 200_000), (998, 1012), (1_188_511_000, 1_188_512_000)] {
+      let expected = (l..=r).filter(|i| is_repeat_pair(*i)).collect::<Vec<_>>();
+      assert_eq!(puzzle1::invalid_ids(l, r), expected);
+      let expected = (l..=r)
+        .filter(|i| is_repeat_any_size(*i))
+        .collect::<Vec<_>>();
+      assert_eq!(puzzle2::invalid_ids(l, r), expected);
+    }
+  }
+
+  #[test]
+  fn generated_ids_match_predicate() {
+    let constraints = [
+      Repetitions::Exactly(3),
+      Repetitions::AtLeast(3),
+      Repetitions::AnyOf(vec![2, 5]),
+      Repetitions::Exactly(1),
+    ];
+    for radix in [2, 3, 10, 16].map(|radix| Radix::new(radix).unwrap()) {
+      for repetitions in &constraints {
+        let expected = (0..=5_000)
+          .filter(|i| is_repeated(*i, repetitions, radix))
+          .collect::<Vec<_>>();
+        assert_eq!(
+          repeated_ids(0, 5_000, repetitions, radix).collect::<Vec<_>>(),
+          expected
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn tallies_match_generated_ids() {
+    let constraints = [
+      Repetitions::Exactly(2),
+      Repetitions::AtLeast(2),
+      Repetitions::Exactly(3),
+      Repetitions::AnyOf(vec![2, 3]),
+      Repetitions::AnyOf(vec![4, 6]),
+      Repetitions::Exactly(1),
+    ];
+    for radix in [2, 3, 10, 16].map(|radix| Radix::new(radix).unwrap()) {
+      for repetitions in &constraints {
+        for (l, r) in [(0, 70_000), (-50, 12), (4_095, 4_096), (70_000, 69_999)] {
+          let ids = repeated_ids(l, r, repetitions, radix).collect::<Vec<_>>();
+          let expected = Tally {
+            count: ids.len() as u64,
+            sum: ids.iter().map(|id| *id as i128).sum(),
+          };
+          assert_eq!(tally_repeated_ids(l, r, repetitions, radix), expected);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn wide_ranges_are_tallied_without_listing() {
+    // Every block of up to 9 digits, written twice.
+    let tally = tally_repeated_ids(
+      1,
+      999_999_999_999_999_999,
+      &Repetitions::Exactly(2),
+      Radix::DECIMAL,
+    );
+    assert_eq!(tally.count, 999_999_999);
+    assert_eq!(
+      tally,
+      (1..=9)
+        .map(|len| tally_repeated_ids(
+          10i64.pow(2 * len - 2),
+          10i64.pow(2 * len) - 1,
+          &Repetitions::Exactly(2),
+          Radix::DECIMAL
+        ))
+        .fold(Tally::default(), Tally::add)
+    );
+    let tally = tally_repeated_ids(
+      1,
+      999_999_999_999_999_999,
+      &Repetitions::AtLeast(2),
+      Radix::DECIMAL,
+    );
+    assert!(tally.count > 999_999_999);
+
+    // Every positive `i64`, summed without overflowing.
+    let binary = Radix::new(2).unwrap();
+    let tally = tally_repeated_ids(i64::MIN, i64::MAX, &Repetitions::Exactly(1), binary);
+    assert_eq!(tally.count, i64::MAX as u64);
+    assert_eq!(tally.sum, i64::MAX as i128 * (i64::MAX as i128 + 1) / 2);
+
+    let input = parse_input("1-999999999999999999").unwrap();
+    assert_eq!(
+      Day2::part1(&input).unwrap(),
+      tally_repeated_ids(
+        1,
+        999_999_999_999_999_999,
+        &Repetitions::Exactly(2),
+        Radix::DECIMAL
+      )
+      .sum
+    );
+  }
+
+  #[test]
+  fn test_is_repeated() {
+    let (binary, hex) = (Radix::new(2).unwrap(), Radix::new(16).unwrap());
+    assert!(is_repeated(
+      111_111,
+      &Repetitions::Exactly(2),
+      Radix::DECIMAL
+    ));
+    assert!(is_repeated(
+      111_111,
+      &Repetitions::Exactly(3),
+      Radix::DECIMAL
+    ));
+    assert!(!is_repeated(
+      111_111,
+      &Repetitions::Exactly(4),
+      Radix::DECIMAL
+    ));
+    assert!(is_repeated(
+      123_123_123,
+      &Repetitions::AnyOf(vec![3]),
+      Radix::DECIMAL
+    ));
+    // 0b101101 is `101` twice, 0xABAB is `AB` twice.
+    assert!(is_repeated(0b101101, &Repetitions::Exactly(2), binary));
+    assert!(is_repeated(0xABAB, &Repetitions::Exactly(2), hex));
+    assert!(!is_repeated(
+      0xABAB,
+      &Repetitions::Exactly(2),
+      Radix::DECIMAL
+    ));
+  }
+
+  #[test]
+  fn test_find_repeated_ids() {
+    let ranges = [(11, 22), (95, 115)];
+    let found = find_repeated_ids(&ranges, &Repetitions::AtLeast(2), Radix::DECIMAL);
+    assert_eq!(found.ids().collect::<Vec<_>>(), vec![11, 22, 99, 111]);
+    assert_eq!(found.tally(), Some(Tally { count: 4, sum: 243 }));
+
+    let found = find_repeated_ids(&ranges, &Repetitions::Exactly(3), Radix::DECIMAL);
+    assert_eq!(found.ids().collect::<Vec<_>>(), vec![111]);
+
+    // Overlapping ranges find 111 twice.
+    let ranges = [(100, 120), (110, 999)];
+    let found = find_repeated_ids(&ranges, &Repetitions::Exactly(3), Radix::DECIMAL);
+    assert_eq!(
+      found.tally(),
+      Some(Tally {
+        count: 10,
+        sum: 111 * 46
+      })
+    );
+
+    let ranges = [(1, i64::MAX); 3];
+    let found = find_repeated_ids(&ranges, &Repetitions::Exactly(1), Radix::DECIMAL);
+    assert_eq!(found.tally(), None);
+  }
+
+  #[test]
+  fn radix_is_at_least_two() {
+    assert_eq!(Radix::new(0), None);
+    assert_eq!(Radix::new(1), None);
+    assert_eq!(Radix::new(2).map(Radix::get), Some(2));
+  }
+
+  #[test]
+  fn ids_built_several_ways_are_counted_once() {
+    assert_eq!(puzzle2::invalid_ids(111_111, 111_111), vec![111_111]);
+    assert_eq!(puzzle1::invalid_ids(111_111, 111_111), vec![111_111]);
+    assert_eq!(
+      puzzle2::invalid_ids(i64::MAX - 10, i64::MAX),
+      Vec::<i64>::new()
+    );
+    assert_eq!(puzzle1::invalid_ids(1, 1_000_000_000_000).len(), 999_999);
+  }
+
+  #[test]
+  fn malformed_pairs_are_parse_errors() {
+    assert!(matches!(
+      parse_input("11-22,95115"),
+      Err(AocError::Parse {
+        line: 1,
+        column: 7,
+        ..
+      })
+    ));
+    assert!(matches!(
+      parse_input("11-22,\n95-x"),
+      Err(AocError::Parse {
+        line: 2,
+        column: 4,
+        ..
+      })
+    ));
   }
 }
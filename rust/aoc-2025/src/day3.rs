@@ -1,22 +1,35 @@
 use std::cmp::Ordering;
-use std::str::FromStr;
 
-use crate::shared::*;
+use crate::error::AocError;
+use crate::shared::column_of;
+use crate::solution::Solution;
 
-fn parse_battery_bank(input: &str) -> Result<Vec<u64>, <u64 as FromStr>::Err> {
-  input
-    .trim()
+fn parse_battery_bank(line_no: usize, input: &str) -> Result<Vec<u64>, AocError> {
+  let trimmed = input.trim();
+  let start_column = column_of(input, trimmed);
+  trimmed
     .chars()
-    .map(|char| char.to_string().parse::<u64>())
+    .zip(start_column..)
+    .map(|(char, column)| {
+      char.to_digit(10).map(u64::from).ok_or_else(|| {
+        AocError::parse(line_no, column, format!("Expected a digit, got '{}'", char))
+      })
+    })
     .collect()
 }
 
-fn maximize_bank(bank: &[u64], size: usize) -> Result<u64, String> {
+fn maximize_bank(bank: &[u64], size: usize) -> Result<u64, AocError> {
+  if bank.len() < size {
+    return Err(AocError::solve(format!(
+      "Must have at least {} elements in a battery bank",
+      size
+    )));
+  }
+
   let indexed = (0..).zip(bank).collect::<Vec<_>>();
   let mut start = 0;
-  let mut end = bank.len() - size;
   let mut digits = vec![];
-  for _ in 1..=size {
+  for end in (bank.len() - size)..bank.len() {
     let selected = indexed[start..=end]
       .iter()
       .max_by(|(i1, x), (i2, y)| match x.cmp(y) {
@@ -27,16 +40,15 @@ fn maximize_bank(bank: &[u64], size: usize) -> Result<u64, String> {
     let (idx, digit) = match selected {
       Some((i, d)) => (*i, **d),
       None => {
-        return Err(format!(
+        return Err(AocError::solve(format!(
           "Must have at least {} elements in a battery bank",
           size
-        ));
+        )));
       }
     };
 
     digits.push(digit);
     start = idx + 1;
-    end += 1;
   }
 
   Ok(
@@ -52,8 +64,9 @@ fn maximize_bank(bank: &[u64], size: usize) -> Result<u64, String> {
 mod puzzle1 {
 
   use super::maximize_bank;
+  use crate::error::AocError;
 
-  pub fn solve(input: &Vec<Vec<u64>>) -> Result<u64, String> {
+  pub fn solve(input: &[Vec<u64>]) -> Result<u64, AocError> {
     let joltages = input
       .iter()
       .map(|bank| maximize_bank(bank, 2))
@@ -66,8 +79,9 @@ mod puzzle1 {
 mod puzzle2 {
 
   use super::maximize_bank;
+  use crate::error::AocError;
 
-  pub fn solve(input: &Vec<Vec<u64>>) -> Result<u64, String> {
+  pub fn solve(input: &[Vec<u64>]) -> Result<u64, AocError> {
     let joltages = input
       .iter()
       .map(|bank| maximize_bank(bank, 12))
@@ -77,20 +91,31 @@ mod puzzle2 {
   }
 }
 
-pub fn run() {
-  let raw_input = load_input_lines("day3.txt").unwrap();
-  let input = raw_input
-    .iter()
-    .map(|line| parse_battery_bank(line).unwrap())
-    .collect::<Vec<_>>();
-
-  let result = puzzle1::solve(&input).unwrap();
-  println!("Day 3 Puzzle 1: {}", result);
-  let result = puzzle2::solve(&input).unwrap();
-  println!("Day 3 Puzzle 2: {}", result);
+pub struct Day3;
+
+impl Solution for Day3 {
+  type Input = Vec<Vec<u64>>;
+  type Output1 = u64;
+  type Output2 = u64;
+
+  fn parse(input: &str) -> Result<Self::Input, AocError> {
+    (1..)
+      .zip(input.lines())
+      .map(|(line_no, line)| parse_battery_bank(line_no, line))
+      .collect()
+  }
+
+  fn part1(input: &Self::Input) -> Result<Self::Output1, AocError> {
+    puzzle1::solve(input)
+  }
+
+  fn part2(input: &Self::Input) -> Result<Self::Output2, AocError> {
+    puzzle2::solve(input)
+  }
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests {
 
   #[cfg(test)]
@@ -99,16 +124,30 @@ mod tests {
     use super::super::*;
     #[test]
     fn test_maximize_bank() {
-      let bank = parse_battery_bank("987654321111111").unwrap();
+      let bank = parse_battery_bank(1, "987654321111111").unwrap();
       assert_eq!(maximize_bank(&bank, 2).unwrap(), 98);
-      let bank = parse_battery_bank("811111111111119").unwrap();
+      let bank = parse_battery_bank(1, "811111111111119").unwrap();
       assert_eq!(maximize_bank(&bank, 2).unwrap(), 89);
-      let bank = parse_battery_bank("234234234234278").unwrap();
+      let bank = parse_battery_bank(1, "234234234234278").unwrap();
       assert_eq!(maximize_bank(&bank, 2).unwrap(), 78);
-      let bank = parse_battery_bank("818181911112111").unwrap();
+      let bank = parse_battery_bank(1, "818181911112111").unwrap();
       assert_eq!(maximize_bank(&bank, 2).unwrap(), 92);
     }
 
+    #[test]
+    fn test_bad_banks() {
+      assert!(matches!(
+        parse_battery_bank(3, "98x1"),
+        Err(AocError::Parse {
+          line: 3,
+          column: 3,
+          ..
+        })
+      ));
+      let bank = parse_battery_bank(1, "9").unwrap();
+      assert!(matches!(maximize_bank(&bank, 2), Err(AocError::Solve(_))));
+    }
+
     #[test]
     fn solve_given_input() {
       let raw_input = vec![
@@ -120,7 +159,7 @@ mod tests {
 
       let input = raw_input
         .iter()
-        .map(|line| parse_battery_bank(line).unwrap())
+        .map(|line| parse_battery_bank(1, line).unwrap())
         .collect::<Vec<Vec<u64>>>();
 
       assert_eq!(solve(&input).unwrap(), 357);
@@ -133,13 +172,13 @@ mod tests {
     use super::super::*;
     #[test]
     fn test_maximize_bank() {
-      let bank = parse_battery_bank("987654321111111").unwrap();
+      let bank = parse_battery_bank(1, "987654321111111").unwrap();
       assert_eq!(maximize_bank(&bank, 12).unwrap(), 987654321111);
-      let bank = parse_battery_bank("811111111111119").unwrap();
+      let bank = parse_battery_bank(1, "811111111111119").unwrap();
       assert_eq!(maximize_bank(&bank, 12).unwrap(), 811111111119);
-      let bank = parse_battery_bank("234234234234278").unwrap();
+      let bank = parse_battery_bank(1, "234234234234278").unwrap();
       assert_eq!(maximize_bank(&bank, 12).unwrap(), 434234234278);
-      let bank = parse_battery_bank("818181911112111").unwrap();
+      let bank = parse_battery_bank(1, "818181911112111").unwrap();
       assert_eq!(maximize_bank(&bank, 12).unwrap(), 888911112111);
     }
 
@@ -154,7 +193,7 @@ mod tests {
 
       let input = raw_input
         .iter()
-        .map(|line| parse_battery_bank(line).unwrap())
+        .map(|line| parse_battery_bank(1, line).unwrap())
         .collect::<Vec<Vec<u64>>>();
 
       assert_eq!(solve(&input).unwrap(), 3121910778619);
@@ -1,120 +1,370 @@
-use std::io;
+use crate::error::AocError;
+use crate::shared::grid::{self, Coord};
+use crate::shared::to_lines;
+use crate::solution::Solution;
 
-use crate::shared::*;
+/// `true` where there is a roll of paper.
+pub type Grid = grid::Grid<bool>;
 
-struct Grid {
-  cells: Vec<Vec<bool>>,
-  width: usize,
-  height: usize,
+fn parse_grid(input: &[String]) -> Result<Grid, AocError> {
+  if input.is_empty() {
+    return Err(AocError::parse(1, 1, "Empty input"));
+  }
+
+  Grid::parse(input, |char| match char {
+    '@' => Some(true),
+    '.' => Some(false),
+    _ => None,
+  })
+}
+
+/// The cells around a roll that count towards its neighbours.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Neighbourhood {
+  /// The 4 orthogonal neighbours.
+  VonNeumann,
+  /// The 8 orthogonal and diagonal neighbours.
+  Moore,
+  /// Every cell within `r` steps in both directions, excluding the roll
+  /// itself; radius 1 is `Moore`.
+  Radius(usize),
+  /// Offsets `(dx, dy)` from the roll, which need not be symmetric.
+  Custom(Vec<(isize, isize)>),
 }
 
-impl Clone for Grid {
-  fn clone(&self) -> Self {
-    Grid {
-      cells: self.cells.clone(),
-      width: self.width,
-      height: self.height,
+impl Neighbourhood {
+  pub fn offsets(&self) -> Vec<(isize, isize)> {
+    match self {
+      Neighbourhood::VonNeumann => grid::VON_NEUMANN.to_vec(),
+      Neighbourhood::Moore => grid::MOORE.to_vec(),
+      Neighbourhood::Radius(r) => {
+        let r = *r as isize;
+        (-r..=r)
+          .flat_map(|dy| (-r..=r).map(move |dx| (dx, dy)))
+          .filter(|offset| *offset != (0, 0))
+          .collect()
+      }
+      Neighbourhood::Custom(offsets) => offsets.clone(),
     }
   }
 }
 
-fn parse_grid(input: &Vec<String>) -> Result<Grid, String> {
-  let mut width = None;
-  let cells = input
-    .iter()
-    .map(|line| {
-      width = match width {
-        None => Some(line.len()),
-        Some(w) => {
-          if w != line.len() {
-            return Err(format!(
-              "Inconsistent line widths: expected {}, got {}",
-              w,
-              line.len()
-            ));
-          }
-          Some(w)
-        }
+/// How neighbours that fall off the edge of the grid are treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+  /// Cells off the grid are empty.
+  Empty,
+  /// Cells off the grid count as paper.
+  WallsArePaper,
+  /// The grid wraps around at its edges.
+  Toroidal,
+}
+
+/// When a roll can be reached by a forklift: fewer than `threshold` of its
+/// neighbours are paper.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+  pub threshold: usize,
+  pub neighbourhood: Neighbourhood,
+  pub boundary: Boundary,
+}
+
+impl Rule {
+  /// The rule from the puzzle, for both parts: fewer than 4 of the 8
+  /// surrounding cells are paper, and there is nothing beyond the edge.
+  pub fn puzzle() -> Self {
+    Rule {
+      threshold: 4,
+      neighbourhood: Neighbourhood::Moore,
+      boundary: Boundary::Empty,
+    }
+  }
+
+  pub fn accessible(&self, coord: Coord, grid: &Grid) -> bool {
+    grid[coord] && self.adjacent_paper(coord, grid) < self.threshold
+  }
+
+  /// How many of the neighbours of `coord` are paper, counting each offset
+  /// separately even if wrapping makes two of them land on the same cell.
+  pub fn adjacent_paper(&self, coord: Coord, grid: &Grid) -> usize {
+    let offsets = self.neighbourhood.offsets();
+    self.count_paper(coord, grid, &offsets)
+  }
+
+  fn count_paper(&self, coord: Coord, grid: &Grid, offsets: &[(isize, isize)]) -> usize {
+    offsets
+      .iter()
+      .filter(|offset| match self.step(coord, **offset, grid) {
+        Some(neighbour) => grid[neighbour],
+        None => self.boundary == Boundary::WallsArePaper,
+      })
+      .count()
+  }
+
+  /// The cell `offset` away from `coord`, or `None` if that is off the grid.
+  fn step(&self, coord: Coord, (dx, dy): (isize, isize), grid: &Grid) -> Option<Coord> {
+    if self.boundary == Boundary::Toroidal {
+      let wrap = |at: usize, delta: isize, size: usize| {
+        (at as isize + delta).rem_euclid(size as isize) as usize
       };
-      Ok(line.chars().map(|char| char == '@').collect::<Vec<bool>>())
-    })
-    .collect::<Result<Vec<Vec<bool>>, String>>()?;
-
-  let height = cells.len();
-
-  match width {
-    Some(w) => Ok(Grid {
-      cells,
-      width: w,
-      height,
-    }),
-    None => Err("Empty input".to_string()),
-  }
-}
-
-fn num_adj_paper(x: usize, y: usize, grid: &Grid) -> usize {
-  vec![
-    (x.checked_sub(1), y.checked_sub(1)),
-    (Some(x), y.checked_sub(1)),
-    (x.checked_add(1), y.checked_sub(1)),
-    (x.checked_sub(1), Some(y)),
-    (x.checked_add(1), Some(y)),
-    (x.checked_sub(1), y.checked_add(1)),
-    (Some(x), y.checked_add(1)),
-    (x.checked_add(1), y.checked_add(1)),
-  ]
-  .iter()
-  .filter_map(|(maybex, maybey)| maybex.and_then(|x| maybey.map(|y| (x, y))))
-  .filter(|(nx, ny)| *nx < grid.width && *ny < grid.height)
-  .map(|(nx, ny)| if grid.cells[ny][nx] { 1 } else { 0 })
-  .sum()
-}
-
-fn candidate_rolls(grid: &Grid) -> Vec<(usize, usize)> {
-  (0..grid.height)
-    .map(move |y| (0..grid.width).map(move |x| (x, y)))
-    .flatten()
-    .filter(|(x, y)| grid.cells[*y][*x] && num_adj_paper(*x, *y, &grid) < 4)
+      return Some(Coord::new(
+        wrap(coord.x, dx, grid.width()),
+        wrap(coord.y, dy, grid.height()),
+      ));
+    }
+
+    coord
+      .offset(dx, dy)
+      .filter(|neighbour| grid.in_bounds(*neighbour))
+  }
+}
+
+impl Default for Rule {
+  fn default() -> Self {
+    Rule::puzzle()
+  }
+}
+
+fn candidate_rolls(grid: &Grid, rule: &Rule) -> Vec<Coord> {
+  grid
+    .coords()
+    .filter(|coord| rule.accessible(*coord, grid))
     .collect()
 }
 
-mod puzzle1 {
+/// Removes accessible rolls round by round, like repeatedly calling
+/// `candidate_rolls` and clearing the result, but without rescanning the
+/// grid. Each roll's count of neighbouring paper is kept up to date, and only
+/// the neighbours of rolls removed in one round can become accessible in the
+/// next, so each round costs time proportional to the rolls it touches.
+pub struct RemovalRounds {
+  rule: Rule,
+  offsets: Vec<(isize, isize)>,
+  grid: Grid,
+  adjacent_paper: grid::Grid<usize>,
+  next_round: Vec<Coord>,
+}
 
-  use super::{Grid, candidate_rolls};
+impl RemovalRounds {
+  pub fn new(grid: &Grid, rule: &Rule) -> Self {
+    let offsets = rule.neighbourhood.offsets();
+    let mut adjacent_paper = grid.map(|_| 0);
+    for coord in grid.coords().filter(|coord| grid[*coord]) {
+      adjacent_paper[coord] = rule.count_paper(coord, grid, &offsets);
+    }
 
-  pub fn solve(grid: &Grid) -> usize {
-    candidate_rolls(grid).len()
+    RemovalRounds {
+      rule: rule.clone(),
+      offsets,
+      grid: grid.clone(),
+      adjacent_paper,
+      next_round: candidate_rolls(grid, rule),
+    }
+  }
+
+  /// The grid with every round so far removed.
+  pub fn grid(&self) -> &Grid {
+    &self.grid
   }
 }
 
-mod puzzle2 {
-  use super::{Grid, candidate_rolls};
+impl Iterator for RemovalRounds {
+  /// The rolls removed in one round.
+  type Item = Vec<Coord>;
+
+  fn next(&mut self) -> Option<Vec<Coord>> {
+    if self.next_round.is_empty() {
+      return None;
+    }
+
+    let removed = std::mem::take(&mut self.next_round);
+    // Clear the whole round first: rolls removed together don't make each
+    // other accessible.
+    for coord in &removed {
+      self.grid[*coord] = false;
+    }
+
+    // A roll counts the removed one as a neighbour if it sits the opposite
+    // way of one of the offsets. Every roll left after a round is at or above
+    // the threshold, so it becomes accessible when its count first drops
+    // below it.
+    let threshold = self.rule.threshold;
+    for coord in &removed {
+      for (dx, dy) in &self.offsets {
+        let Some(dependent) = self.rule.step(*coord, (-dx, -dy), &self.grid) else {
+          continue;
+        };
+        if self.grid[dependent] {
+          let count = &mut self.adjacent_paper[dependent];
+          *count -= 1;
+          if *count + 1 == threshold {
+            self.next_round.push(dependent);
+          }
+        }
+      }
+    }
+
+    Some(removed)
+  }
+}
+
+/// A full record of the removal process: which rolls went in each round,
+/// and when each cell was cleared.
+pub struct RemovalHistory {
+  initial: Grid,
+  rounds: Vec<Vec<Coord>>,
+  removed_in: grid::Grid<Option<usize>>,
+  final_grid: Grid,
+}
+
+impl RemovalHistory {
+  /// Runs `RemovalRounds` to completion, recording every round.
+  pub fn record(grid: &Grid, rule: &Rule) -> Self {
+    let mut removal = RemovalRounds::new(grid, rule);
+    let rounds = removal.by_ref().collect::<Vec<_>>();
 
-  pub fn iterate_grid(grid: &mut Grid) -> usize {
-    let candidates = candidate_rolls(grid);
-    for (x, y) in candidates.iter() {
-      grid.cells[*y][*x] = false;
+    let mut removed_in = grid.map(|_| None);
+    for (round, coords) in (1..).zip(&rounds) {
+      for coord in coords {
+        removed_in[*coord] = Some(round);
+      }
     }
-    candidates.len()
+
+    RemovalHistory {
+      initial: grid.clone(),
+      rounds,
+      removed_in,
+      final_grid: removal.grid().clone(),
+    }
+  }
+
+  /// The rolls removed in each round; round `n` is `rounds()[n - 1]`.
+  pub fn rounds(&self) -> &[Vec<Coord>] {
+    &self.rounds
   }
 
-  pub fn solve(grid: &mut Grid) -> usize {
-    let mut count = 0;
-    while candidate_rolls(grid).len() > 0 {
-      count += iterate_grid(grid);
+  /// The round in which the roll at `coord` was removed, or `None` if it was
+  /// never there or never removed.
+  pub fn removed_in(&self, coord: Coord) -> Option<usize> {
+    self.removed_in.get(coord).copied().flatten()
+  }
+
+  pub fn total_removed(&self) -> usize {
+    self.rounds.iter().map(Vec::len).sum()
+  }
+
+  /// The grid once no more rolls can be removed.
+  pub fn final_grid(&self) -> &Grid {
+    &self.final_grid
+  }
+
+  /// Every cell as it looks at the end of `round`, where round 0 is the
+  /// initial grid.
+  fn frame_cells(&self, round: usize) -> grid::Grid<FrameCell> {
+    let mut frame = self.initial.map(|_| FrameCell::Empty);
+    for coord in self.initial.coords().filter(|coord| self.initial[*coord]) {
+      frame[coord] = match self.removed_in[coord] {
+        Some(removed) if removed == round => FrameCell::JustRemoved,
+        Some(removed) if removed < round => FrameCell::Empty,
+        _ => FrameCell::Paper,
+      };
+    }
+    frame
+  }
+
+  /// Renders the end of `round` in the puzzle's notation, marking the rolls
+  /// removed in that round with `x`.
+  pub fn ascii_frame(&self, round: usize) -> String {
+    self.frame_cells(round).render(|cell| match cell {
+      FrameCell::Empty => '.',
+      FrameCell::Paper => '@',
+      FrameCell::JustRemoved => 'x',
+    })
+  }
+
+  /// Every frame from the initial grid to the final one, separated by blank
+  /// lines.
+  pub fn ascii_frames(&self) -> String {
+    (0..=self.rounds.len())
+      .map(|round| self.ascii_frame(round))
+      .collect::<Vec<_>>()
+      .join("\n\n")
+  }
+
+  /// Renders the end of `round` as a binary PPM (P6) image, drawing each cell
+  /// as a `scale` × `scale` square.
+  pub fn ppm_frame(&self, round: usize, scale: usize) -> Vec<u8> {
+    let frame = self.frame_cells(round);
+    let (width, height) = (frame.width() * scale, frame.height() * scale);
+
+    let mut ppm = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    for row in frame.rows() {
+      let pixels = row
+        .iter()
+        .flat_map(|cell| std::iter::repeat_n(cell.colour(), scale))
+        .flatten()
+        .collect::<Vec<_>>();
+      for _ in 0..scale {
+        ppm.extend_from_slice(&pixels);
+      }
+    }
+    ppm
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FrameCell {
+  Empty,
+  Paper,
+  JustRemoved,
+}
+
+impl FrameCell {
+  fn colour(self) -> [u8; 3] {
+    match self {
+      FrameCell::Empty => [255, 255, 255],
+      FrameCell::Paper => [64, 64, 64],
+      FrameCell::JustRemoved => [220, 40, 40],
     }
-    count
   }
 }
 
-pub fn run() -> io::Result<()> {
-  let input_lines = load_input_lines("day4.txt")?;
-  let mut inputs = parse_grid(&input_lines).expect("Unable to parse input file");
+mod puzzle1 {
+  use super::{Grid, Rule, candidate_rolls};
+
+  pub fn solve(grid: &Grid) -> usize {
+    candidate_rolls(grid, &Rule::puzzle()).len()
+  }
+}
+
+mod puzzle2 {
+  use super::{Grid, RemovalRounds, Rule};
+
+  pub fn solve(grid: &Grid) -> usize {
+    RemovalRounds::new(grid, &Rule::puzzle())
+      .map(|round| round.len())
+      .sum()
+  }
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+  type Input = Grid;
+  type Output1 = usize;
+  type Output2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input, AocError> {
+    parse_grid(&to_lines(input))
+  }
 
-  println!("Day 4 Puzzle 1: {}", puzzle1::solve(&inputs));
-  println!("Day 4 Puzzle 2: {}", puzzle2::solve(&mut inputs));
+  fn part1(input: &Self::Input) -> Result<Self::Output1, AocError> {
+    Ok(puzzle1::solve(input))
+  }
 
-  Ok(())
+  fn part2(input: &Self::Input) -> Result<Self::Output2, AocError> {
+    Ok(puzzle2::solve(input))
+  }
 }
 
 #[cfg(test)]
@@ -143,8 +393,8 @@ mod tests {
   #[test]
   fn test_parse_grid() {
     let ans = parse_grid(&SAMPLE_INPUT).unwrap();
-    assert!(ans.height == 10);
-    assert!(ans.width == 10);
+    assert!(ans.height() == 10);
+    assert!(ans.width() == 10);
 
     let input = vec![
       "..@@@@.".to_string(),
@@ -160,30 +410,206 @@ mod tests {
     ];
 
     let ans = parse_grid(&input);
-    assert!(ans.is_err());
+    assert!(matches!(
+      ans,
+      Err(AocError::Parse {
+        line: 2,
+        column: 8,
+        ..
+      })
+    ));
+  }
+
+  #[test]
+  fn test_adjacent_paper() {
+    let rule = Rule::puzzle();
+    assert_eq!(rule.adjacent_paper(Coord::new(0, 0), &SAMPLE_GRID), 2);
+    assert_eq!(rule.adjacent_paper(Coord::new(0, 2), &SAMPLE_GRID), 4);
+    assert_eq!(rule.adjacent_paper(Coord::new(4, 4), &SAMPLE_GRID), 8);
   }
 
   #[test]
-  fn test_num_adj_paper() {
-    assert_eq!(num_adj_paper(0, 0, &SAMPLE_GRID), 2);
-    assert_eq!(num_adj_paper(0, 2, &SAMPLE_GRID), 4);
-    assert_eq!(num_adj_paper(4, 4, &SAMPLE_GRID), 8);
+  fn test_rule_variants() {
+    assert_eq!(Neighbourhood::Radius(1).offsets().len(), 8);
+    assert_eq!(Neighbourhood::Radius(2).offsets().len(), 24);
+
+    let corner = Coord::new(0, 0);
+    let rule = |neighbourhood, boundary| Rule {
+      threshold: 4,
+      neighbourhood,
+      boundary,
+    };
+    let walls = rule(Neighbourhood::Moore, Boundary::WallsArePaper);
+    assert_eq!(walls.adjacent_paper(corner, &SAMPLE_GRID), 7);
+    let von_neumann = rule(Neighbourhood::VonNeumann, Boundary::Empty);
+    assert_eq!(von_neumann.adjacent_paper(corner, &SAMPLE_GRID), 1);
+    // Wrapping adds the paper at (9, 1) and (0, 9).
+    let torus = rule(Neighbourhood::Moore, Boundary::Toroidal);
+    assert_eq!(torus.adjacent_paper(corner, &SAMPLE_GRID), 4);
+    let right = rule(Neighbourhood::Custom(vec![(1, 0), (2, 0)]), Boundary::Empty);
+    assert_eq!(right.adjacent_paper(corner, &SAMPLE_GRID), 1);
+
+    assert!(candidate_rolls(&SAMPLE_GRID, &walls).len() < 13);
+    assert!(candidate_rolls(&SAMPLE_GRID, &von_neumann).len() > 13);
   }
 
   #[test]
   fn test_candidate_rolls() {
-    assert_eq!(candidate_rolls(&SAMPLE_GRID).len(), 13);
+    assert_eq!(candidate_rolls(&SAMPLE_GRID, &Rule::puzzle()).len(), 13);
+  }
+
+  #[test]
+  fn test_removal_history() {
+    let history = RemovalHistory::record(&SAMPLE_GRID, &Rule::puzzle());
+    assert_eq!(history.total_removed(), 43);
+    assert_eq!(history.rounds()[0].len(), 13);
+    assert_eq!(history.removed_in(Coord::new(2, 0)), Some(1));
+    assert_eq!(history.removed_in(Coord::new(0, 0)), None);
+    assert_eq!(history.removed_in(Coord::new(4, 4)), None);
+    assert_eq!(history.removed_in(Coord::new(10, 10)), None);
+
+    let remaining = history
+      .final_grid()
+      .coords()
+      .filter(|coord| history.final_grid()[*coord])
+      .count();
+    let initial = SAMPLE_GRID
+      .coords()
+      .filter(|coord| SAMPLE_GRID[*coord])
+      .count();
+    assert_eq!(initial - remaining, 43);
+    assert!(candidate_rolls(history.final_grid(), &Rule::puzzle()).is_empty());
+  }
+
+  #[test]
+  fn test_ascii_frames() {
+    let history = RemovalHistory::record(&SAMPLE_GRID, &Rule::puzzle());
+    assert_eq!(history.ascii_frame(0), SAMPLE_INPUT.join("\n"));
+    assert_eq!(
+      history.ascii_frame(1),
+      [
+        "..xx.xx@x.",
+        "x@@.@.@.@@",
+        "@@@@@.x.@@",
+        "@.@@@@..@.",
+        "x@.@@@@.@x",
+        ".@@@@@@@.@",
+        ".@.@.@.@@@",
+        "x.@@@.@@@@",
+        ".@@@@@@@@.",
+        "x.x.@@@.x.",
+      ]
+      .join("\n")
+    );
+
+    let last = history.rounds().len();
+    assert_eq!(
+      history.ascii_frame(last + 1),
+      history
+        .final_grid()
+        .render(|paper| if *paper { '@' } else { '.' })
+    );
+    assert_eq!(history.ascii_frames().split("\n\n").count(), last + 1);
+  }
+
+  #[test]
+  fn test_ppm_frame() {
+    let history = RemovalHistory::record(&SAMPLE_GRID, &Rule::puzzle());
+    let ppm = history.ppm_frame(1, 2);
+    let header = b"P6\n20 20\n255\n";
+    assert_eq!(&ppm[..header.len()], header);
+    assert_eq!(ppm.len(), header.len() + 20 * 20 * 3);
+
+    // Row 0 starts `..xx.xx@`: (0, 0) is empty, (2, 0) was removed in round
+    // 1 and (7, 0) is still there.
+    let pixel = |x: usize, y: usize| &ppm[header.len() + (y * 20 + x) * 3..][..3];
+    assert_eq!(pixel(0, 0), [255, 255, 255]);
+    assert_eq!(pixel(5, 1), [220, 40, 40]);
+    assert_eq!(pixel(15, 1), [64, 64, 64]);
   }
 
   #[cfg(test)]
   mod puzzle2 {
     use super::super::puzzle2::*;
+    use super::super::*;
     use super::SAMPLE_GRID;
+    use crate::shared::random::Lcg;
+
+    /// Removes every accessible roll by rescanning the whole grid each round.
+    fn rescan_rounds(grid: &Grid, rule: &Rule) -> Vec<Vec<Coord>> {
+      let mut grid = grid.clone();
+      let mut rounds = vec![];
+      loop {
+        let candidates = candidate_rolls(&grid, rule);
+        if candidates.is_empty() {
+          return rounds;
+        }
+        for coord in &candidates {
+          grid[*coord] = false;
+        }
+        rounds.push(candidates);
+      }
+    }
+
+    fn sorted(rounds: Vec<Vec<Coord>>) -> Vec<Vec<Coord>> {
+      rounds
+        .into_iter()
+        .map(|mut round| {
+          round.sort();
+          round
+        })
+        .collect()
+    }
 
     #[test]
     fn test_solve() {
-      let mut grid = SAMPLE_GRID.clone();
-      assert_eq!(solve(&mut grid), 43);
+      assert_eq!(solve(&SAMPLE_GRID), 43);
+    }
+
+    #[test]
+    fn test_rounds_match_rescanning() {
+      let puzzle = Rule::puzzle();
+      let rounds = RemovalRounds::new(&SAMPLE_GRID, &puzzle).collect::<Vec<_>>();
+      assert_eq!(rounds[0].len(), 13);
+      assert_eq!(sorted(rounds), sorted(rescan_rounds(&SAMPLE_GRID, &puzzle)));
+
+      // A larger pseudo-random grid, about two thirds paper.
+      let mut random = Lcg::new(12345);
+      let mut grid = Grid::new(80, 60, false);
+      for coord in grid.coords().collect::<Vec<_>>() {
+        grid[coord] = !random.next().unwrap().is_multiple_of(3);
+      }
+
+      let rules = [
+        puzzle,
+        Rule {
+          threshold: 3,
+          neighbourhood: Neighbourhood::VonNeumann,
+          boundary: Boundary::WallsArePaper,
+        },
+        Rule {
+          threshold: 12,
+          neighbourhood: Neighbourhood::Radius(2),
+          boundary: Boundary::Toroidal,
+        },
+        Rule {
+          threshold: 2,
+          neighbourhood: Neighbourhood::Custom(vec![(1, 0), (2, 1), (0, -3)]),
+          boundary: Boundary::Toroidal,
+        },
+      ];
+      for rule in &rules {
+        let mut removal = RemovalRounds::new(&grid, rule);
+        let rounds = removal.by_ref().collect::<Vec<_>>();
+        assert!(rounds.len() > 1, "{:?}", rule);
+        assert_eq!(
+          sorted(rounds),
+          sorted(rescan_rounds(&grid, rule)),
+          "{:?}",
+          rule
+        );
+        assert!(candidate_rolls(removal.grid(), rule).is_empty());
+      }
     }
   }
 }
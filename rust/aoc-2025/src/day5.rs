@@ -1,57 +1,49 @@
-use std::io;
-
+use crate::error::AocError;
 use crate::shared;
+use crate::shared::range_set::{RangeSet, parse_range};
+use crate::solution::Solution;
 
-struct PuzzleInput {
-  fresh_ranges: Vec<(i64, i64)>,
+pub struct PuzzleInput {
+  fresh: RangeSet,
   ingredients: Vec<i64>,
 }
 
-fn parse_input(input: &Vec<String>) -> Result<PuzzleInput, String> {
+fn parse_input(input: &[String]) -> Result<PuzzleInput, AocError> {
   let (_, split_index) = input
     .iter()
     .zip(0..)
     .find(|(item, _)| item.trim().is_empty())
-    .ok_or("No split found between ranges and ingredients.".to_string())?;
-
-  let mut fresh_ranges = input.clone();
-  let ingredients = fresh_ranges.split_off(split_index);
-  let fresh_ranges = fresh_ranges;
-
-  let ingredients = ingredients
-    .iter()
-    .filter(|line| !line.trim().is_empty())
-    .map(|line| {
-      line
-        .trim()
-        .parse::<i64>()
-        .map_err(|e| format!("Failed to parse ingredient '{}': {}", line, e))
+    .ok_or_else(|| {
+      AocError::parse(
+        input.len() + 1,
+        1,
+        "No split found between ranges and ingredients.",
+      )
+    })?;
+
+  let numbered = (1..).zip(input).filter(|(_, line)| !line.trim().is_empty());
+
+  let ingredients = numbered
+    .clone()
+    .skip_while(|(line_no, _)| *line_no <= split_index)
+    .map(|(line_no, line)| {
+      let trimmed = line.trim();
+      trimmed.parse::<i64>().map_err(|e| {
+        AocError::parse(
+          line_no,
+          shared::column_of(line, trimmed),
+          format!("Failed to parse ingredient '{}': {}", line, e),
+        )
+      })
     })
     .collect::<Result<_, _>>()?;
 
-  let fresh_ranges = fresh_ranges
-    .iter()
-    .filter(|line| !line.trim().is_empty())
-    .map(|line| {
-      let parts = line
-        .trim()
-        .split('-')
-        .map(|part| part.trim().parse::<i64>())
-        .collect::<Result<Vec<i64>, _>>()
-        .map_err(|e| format!("Failed to parse range '{}': {}", line, e))?;
-
-      if parts.len() != 2 {
-        return Err(format!("Invalid range format: '{}'", line));
-      }
-
-      Ok((parts[0], parts[1]))
-    })
+  let fresh = numbered
+    .take_while(|(line_no, _)| *line_no <= split_index)
+    .map(|(line_no, line)| parse_range(line_no, line, line))
     .collect::<Result<_, _>>()?;
 
-  Ok(PuzzleInput {
-    fresh_ranges,
-    ingredients,
-  })
+  Ok(PuzzleInput { fresh, ingredients })
 }
 
 mod puzzle1 {
@@ -60,70 +52,37 @@ mod puzzle1 {
     input
       .ingredients
       .iter()
-      .filter(|ingredient| {
-        input
-          .fresh_ranges
-          .iter()
-          .any(|(start, end)| *ingredient >= start && *ingredient <= end)
-      })
+      .filter(|ingredient| input.fresh.contains(**ingredient))
       .count()
   }
 }
 
 mod puzzle2 {
   use super::PuzzleInput;
-  fn union_range(range1: (i64, i64), range2: (i64, i64)) -> Option<(i64, i64)> {
-    if range1.1 < range2.0 || range2.1 < range1.0 {
-      None
-    } else {
-      Some((range1.0.min(range2.0), range1.1.max(range2.1)))
-    }
-  }
 
   pub fn solve(input: &PuzzleInput) -> usize {
-    let mut ranges = input.fresh_ranges.clone();
-    ranges.sort();
-
-    let mut merged_ranges = vec![];
-    {
-      let mut current_range = None;
-      for range in ranges {
-        match current_range {
-          None => current_range = Some(range),
-          Some(rng) => match union_range(rng, range) {
-            Some(unioned) => current_range = Some(unioned),
-            None => {
-              merged_ranges.push(rng);
-              current_range = Some(range);
-            }
-          },
-        }
-      }
-
-      if let Some(current_range) = current_range {
-        merged_ranges.push(current_range);
-      }
-    }
-
-    let merged_ranges = merged_ranges;
-
-    merged_ranges
-      .iter()
-      .map(|(start, end)| end - start + 1)
-      .sum::<i64>() as usize
+    input.fresh.total_length() as usize
   }
 }
 
-pub fn run() -> io::Result<()> {
-  let input_lines = shared::load_input_lines("day5.txt")?;
-  let puzzle_input = parse_input(&input_lines).unwrap();
+pub struct Day5;
+
+impl Solution for Day5 {
+  type Input = PuzzleInput;
+  type Output1 = usize;
+  type Output2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input, AocError> {
+    parse_input(&shared::to_lines(input))
+  }
 
-  let result1 = puzzle1::solve(&puzzle_input);
-  println!("Day 5, Puzzle 1: {}", result1);
-  let result2 = puzzle2::solve(&puzzle_input);
-  println!("Day 5, Puzzle 2: {}", result2);
+  fn part1(input: &Self::Input) -> Result<Self::Output1, AocError> {
+    Ok(puzzle1::solve(input))
+  }
 
-  Ok(())
+  fn part2(input: &Self::Input) -> Result<Self::Output2, AocError> {
+    Ok(puzzle2::solve(input))
+  }
 }
 
 #[cfg(test)]
@@ -150,11 +109,19 @@ mod tests {
   #[test]
   fn test_parse_input() {
     let puzzle_input = parse_input(&SAMPLE_INPUT).unwrap();
-    assert_eq!(
-      puzzle_input.fresh_ranges,
-      vec![(3, 5), (10, 14), (16, 20), (12, 18)]
-    );
+    assert_eq!(puzzle_input.fresh.ranges(), &[(3, 5), (10, 20)]);
     assert_eq!(puzzle_input.ingredients, vec![1, 5, 8, 11, 17, 32]);
+
+    let mut bad_input = SAMPLE_INPUT.clone();
+    bad_input[2] = "16-2x".to_string();
+    assert!(matches!(
+      parse_input(&bad_input),
+      Err(AocError::Parse {
+        line: 3,
+        column: 4,
+        ..
+      })
+    ));
   }
 
   #[cfg(test)]
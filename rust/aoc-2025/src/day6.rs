@@ -1,161 +1,556 @@
+use std::ops::Range;
+
+use crate::error::AocError;
 use crate::shared;
+use crate::solution::{Options, Solution};
+use expression::{EvalError, Expr};
+use strum_macros::{Display, EnumString};
+
+pub mod expression;
 
 #[derive(Debug)]
-struct MathProblem {
-  operands: Vec<i64>,
-  operator: fn(i64, i64) -> i64,
-  monoid_identity: i64,
+pub struct MathProblem {
+  /// Usually plain numbers, but any cell may hold an expression.
+  operands: Vec<Expr>,
+  operator: Operator,
+  /// The column of the operator, to point at the problem in errors.
+  column: usize,
 }
 
-fn add(x: i64, y: i64) -> i64 {
-  x + y
+/// A worksheet operator. Every problem is a left fold starting from its
+/// first operand, so `10 3 -` is `10 - 3` and `10 3 2 /` is `(10 / 3) / 2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+pub enum Operator {
+  #[strum(serialize = "+")]
+  Add,
+  #[strum(serialize = "-")]
+  Subtract,
+  #[strum(serialize = "*")]
+  Multiply,
+  #[strum(serialize = "/")]
+  Divide,
 }
-fn multiply(x: i64, y: i64) -> i64 {
-  x * y
+
+/// Why an operator couldn't be applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithmeticError {
+  Overflow,
+  DivisionByZero,
 }
-fn subtract(x: i64, y: i64) -> i64 {
-  x - y
+
+impl Operator {
+  pub fn apply(
+    self,
+    lhs: Rational,
+    rhs: Rational,
+    evaluation: Evaluation,
+  ) -> Result<Rational, ArithmeticError> {
+    let result = match self {
+      Operator::Add => lhs.checked_add(rhs),
+      Operator::Subtract => lhs.checked_sub(rhs),
+      Operator::Multiply => lhs.checked_mul(rhs),
+      Operator::Divide if rhs.is_zero() => return Err(ArithmeticError::DivisionByZero),
+      Operator::Divide => match evaluation.division {
+        Division::Integer => lhs.checked_div_truncating(rhs),
+        Division::Rational => lhs.checked_div(rhs),
+      },
+    };
+
+    result
+      .filter(|value| evaluation.precision.fits(*value))
+      .ok_or(ArithmeticError::Overflow)
+  }
 }
-fn divide(x: i64, y: i64) -> i64 {
-  x / y
+
+/// An exact fraction, kept in lowest terms with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rational {
+  numerator: i128,
+  denominator: i128,
 }
 
-mod puzzle1 {
-  use super::MathProblem;
-  use super::{add, divide, multiply, subtract};
+impl Rational {
+  pub const ZERO: Rational = Rational::integer(0);
 
-  pub fn parse_input(input: &Vec<String>) -> Result<Vec<MathProblem>, String> {
-    let all_operands = input[..input.len() - 1]
-      .iter()
-      .map(|line| {
-        line
-          .trim()
-          .split_whitespace()
-          .map(|token| {
-            token
-              .trim()
-              .parse::<i64>()
-              .map_err(|e| format!("Failed to parse operand '{}': {}", token, e))
-          })
-          .collect::<Result<Vec<i64>, _>>()
+  pub const fn integer(value: i128) -> Self {
+    Rational {
+      numerator: value,
+      denominator: 1,
+    }
+  }
+
+  /// `numerator / denominator` in lowest terms, or `None` if the denominator
+  /// is zero or the result doesn't fit.
+  pub fn new(numerator: i128, denominator: i128) -> Option<Self> {
+    if denominator == 0 {
+      return None;
+    }
+
+    let divisor = gcd(numerator, denominator);
+    let (numerator, denominator) = (numerator / divisor, denominator / divisor);
+    if denominator < 0 {
+      Some(Rational {
+        numerator: numerator.checked_neg()?,
+        denominator: denominator.checked_neg()?,
       })
-      .collect::<Result<Vec<Vec<i64>>, String>>()?;
-
-    let operator_line = input[input.len() - 1]
-      .split_whitespace()
-      .map(|token| match token.trim() {
-        "+" => Ok((add as fn(i64, i64) -> i64, 0)),
-        "*" => Ok((multiply as fn(i64, i64) -> i64, 1)),
-        "-" => Ok((subtract as fn(i64, i64) -> i64, 0)),
-        "/" => Ok((divide as fn(i64, i64) -> i64, 1)),
-        _ => Err(format!("Unknown operator '{}'", token)),
+    } else {
+      Some(Rational {
+        numerator,
+        denominator,
       })
-      .collect::<Result<Vec<(fn(i64, i64) -> i64, i64)>, String>>()?;
-
-    Ok(
-      (0..operator_line.len())
-        .map(|i| {
-          let operands = all_operands.iter().map(|ops| ops[i]).collect();
-          let (operator, monoid_identity) = operator_line[i];
-          MathProblem {
-            operands,
-            operator,
-            monoid_identity,
-          }
-        })
-        .collect::<Vec<MathProblem>>(),
+    }
+  }
+
+  pub fn numerator(self) -> i128 {
+    self.numerator
+  }
+
+  pub fn denominator(self) -> i128 {
+    self.denominator
+  }
+
+  pub fn is_zero(self) -> bool {
+    self.numerator == 0
+  }
+
+  /// The value as an integer, if it is one.
+  pub fn to_integer(self) -> Option<i128> {
+    (self.denominator == 1).then_some(self.numerator)
+  }
+
+  pub fn checked_neg(self) -> Option<Self> {
+    Some(Rational {
+      numerator: self.numerator.checked_neg()?,
+      denominator: self.denominator,
+    })
+  }
+
+  pub fn checked_add(self, other: Rational) -> Option<Self> {
+    // Scaling to the lcm of the denominators keeps the terms small.
+    let divisor = gcd(self.denominator, other.denominator);
+    let lhs = self.numerator.checked_mul(other.denominator / divisor)?;
+    let rhs = other.numerator.checked_mul(self.denominator / divisor)?;
+    Rational::new(
+      lhs.checked_add(rhs)?,
+      (self.denominator / divisor).checked_mul(other.denominator)?,
     )
   }
+
+  pub fn checked_sub(self, other: Rational) -> Option<Self> {
+    self.checked_add(other.checked_neg()?)
+  }
+
+  pub fn checked_mul(self, other: Rational) -> Option<Self> {
+    // Cancelling across the fractions first keeps the products small.
+    let left = gcd(self.numerator, other.denominator);
+    let right = gcd(other.numerator, self.denominator);
+    Rational::new(
+      (self.numerator / left).checked_mul(other.numerator / right)?,
+      (self.denominator / right).checked_mul(other.denominator / left)?,
+    )
+  }
+
+  pub fn checked_div(self, other: Rational) -> Option<Self> {
+    let reciprocal = Rational::new(other.denominator, other.numerator)?;
+    self.checked_mul(reciprocal)
+  }
+
+  /// Divides and rounds toward zero, like `/` on integers.
+  pub fn checked_div_truncating(self, other: Rational) -> Option<Self> {
+    let quotient = self.checked_div(other)?;
+    Some(Rational::integer(quotient.numerator / quotient.denominator))
+  }
 }
 
-mod puzzle2 {
-  use super::MathProblem;
-  use super::{add, divide, multiply, subtract};
-
-  pub fn parse_input(input: &Vec<String>) -> Result<Vec<MathProblem>, String> {
-    let operator_line = &input[input.len() - 1];
-
-    let operators = operator_line
-      .split_whitespace()
-      .map(|token| match token.trim() {
-        "+" => Ok((add as fn(i64, i64) -> i64, 0)),
-        "*" => Ok((multiply as fn(i64, i64) -> i64, 1)),
-        "-" => Ok((subtract as fn(i64, i64) -> i64, 0)),
-        "/" => Ok((divide as fn(i64, i64) -> i64, 1)),
-        _ => Err(format!("Unknown operator '{}'", token)),
-      })
-      .collect::<Result<Vec<(fn(i64, i64) -> i64, i64)>, String>>()?;
-
-    let operand_lines = &input[..input.len() - 1];
-    let mut all_operands = vec![];
-    let mut current_problem = vec![];
-    for col in 0..operator_line.len() {
-      let num_str = operand_lines
-        .iter()
-        .map(|line| line.chars().nth(col).unwrap_or(' '))
-        .collect::<String>()
-        .trim()
-        .to_string();
-
-      if num_str.is_empty() {
-        all_operands.push(current_problem);
-        current_problem = vec![];
-      } else {
-        let num = num_str
-          .parse::<i64>()
-          .map_err(|e| format!("Failed to parse operand in column {}: {}", col, e))?;
-
-        current_problem.push(num);
-      }
+impl std::fmt::Display for Rational {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self.to_integer() {
+      Some(value) => write!(f, "{}", value),
+      None => write!(f, "{}/{}", self.numerator, self.denominator),
     }
+  }
+}
+
+/// The greatest common divisor, which is 1 rather than 0 when both are 0 so
+/// it is always safe to divide by.
+fn gcd(a: i128, b: i128) -> i128 {
+  let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+  while b != 0 {
+    (a, b) = (b, a % b);
+  }
+  // Only `gcd(i128::MIN, i128::MIN)` or `gcd(i128::MIN, 0)` is too big, and
+  // halving it still divides both.
+  i128::try_from(a.max(1)).unwrap_or(i128::MIN / -2)
+}
 
-    if current_problem.len() > 0 {
-      all_operands.push(current_problem);
+/// The integer width answers are computed in. Every intermediate result must
+/// fit, so a result is either exact or an error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Display, EnumString)]
+pub enum Precision {
+  #[default]
+  #[strum(serialize = "i64")]
+  I64,
+  #[strum(serialize = "i128")]
+  I128,
+}
+
+impl Precision {
+  fn fits(self, value: Rational) -> bool {
+    self == Precision::I128
+      || (i64::try_from(value.numerator).is_ok() && i64::try_from(value.denominator).is_ok())
+  }
+}
+
+/// What `/` means on a worksheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Division {
+  /// Integer division, rounding toward zero.
+  Integer,
+  /// Exact division, giving a fraction.
+  Rational,
+}
+
+/// How problems are evaluated. The default matches the puzzle: `i64`
+/// arithmetic with integer division. The runner takes the precision from
+/// `--precision`; rational division is only available by calling `solve`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Evaluation {
+  pub precision: Precision,
+  pub division: Division,
+}
+
+impl Default for Evaluation {
+  fn default() -> Self {
+    Evaluation {
+      precision: Precision::I64,
+      division: Division::Integer,
     }
+  }
+}
 
-    Ok(
-      all_operands
-        .iter()
-        .zip(operators)
-        .map(|(operand, (operator, identity))| MathProblem {
-          operands: operand.clone(),
-          operator,
-          monoid_identity: identity,
-        })
-        .collect(),
+impl MathProblem {
+  pub fn evaluate(&self, evaluation: Evaluation) -> Result<Rational, AocError> {
+    let error = |EvalError { error, step }| {
+      let problem = match error {
+        ArithmeticError::Overflow => format!("overflows {}", evaluation.precision),
+        ArithmeticError::DivisionByZero => "divides by zero".to_string(),
+      };
+      AocError::solve(format!(
+        "Problem at column {} {}: {}",
+        self.column, problem, step
+      ))
+    };
+
+    let Some((first, rest)) = self.operands.split_first() else {
+      return Err(AocError::solve(format!(
+        "Problem at column {} has no operands",
+        self.column
+      )));
+    };
+
+    rest.iter().try_fold(
+      first.evaluate(evaluation).map_err(error)?,
+      |acc, operand| {
+        let operand = operand.evaluate(evaluation).map_err(error)?;
+        self
+          .operator
+          .apply(acc, operand, evaluation)
+          .map_err(|e| EvalError {
+            error: e,
+            step: format!("{} {} {}", acc, self.operator, operand),
+          })
+          .map_err(error)
+      },
     )
   }
 }
 
-fn solve(input: &Vec<MathProblem>) -> i64 {
-  input
-    .iter()
-    .map(|problem| {
-      problem
-        .operands
-        .iter()
-        .fold(problem.monoid_identity, |acc, &op| {
-          (problem.operator)(acc, op)
-        })
+/// Parses one cell, where `position(offset)` gives the line and column of the
+/// character `offset` into `text`.
+fn parse_cell(text: &str, position: impl Fn(usize) -> (usize, usize)) -> Result<Expr, AocError> {
+  expression::parse(text).map_err(|e| {
+    let (line, column) = position(e.offset);
+    AocError::parse(
+      line,
+      column,
+      format!("Failed to parse operand '{}': {}", text.trim(), e.message),
+    )
+  })
+}
+
+/// Splits a worksheet into its operand lines and its (last) operator line.
+fn split_worksheet(input: &[String]) -> Result<(&[String], &String), AocError> {
+  match input.split_last() {
+    Some((operator_line, operand_lines)) => Ok((operand_lines, operator_line)),
+    None => Err(AocError::parse(1, 1, "Empty worksheet")),
+  }
+}
+
+/// The order a problem's cells are read in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadingOrder {
+  /// Each line is a cell, from the top line down.
+  RowWise,
+  /// Each line is a cell, from the bottom line up.
+  BottomUp,
+  /// Each column, read top to bottom, is a cell, from the leftmost column.
+  ColumnWise,
+  /// Each column, read top to bottom, is a cell, from the rightmost column.
+  RightToLeft,
+}
+
+/// A run of columns between blank ones, and its operator and the operator's
+/// 1-based column, if it has one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Block {
+  columns: Range<usize>,
+  operator: Option<(Operator, usize)>,
+}
+
+/// A worksheet split into problems, ready to be read in any order. Lines may
+/// have different lengths; missing characters are read as spaces.
+#[derive(Debug, Clone)]
+pub struct Worksheet {
+  /// The operand lines, padded to the same width.
+  rows: Vec<Vec<char>>,
+  blocks: Vec<Block>,
+  /// The line number of the operators, for errors.
+  operator_line: usize,
+}
+
+impl Worksheet {
+  /// Finds the runs of columns separated by columns that are blank on every
+  /// line, operators included.
+  ///
+  /// Read by column, each run is a problem. Read by row, a cell may hold an
+  /// expression with spaces in it, so a problem runs from its operator up to
+  /// the next run with an operator: runs without one belong to the problem
+  /// on their left.
+  pub fn parse(input: &[String]) -> Result<Self, AocError> {
+    let (operand_lines, operator_line) = split_worksheet(input)?;
+    let width = input
+      .iter()
+      .map(|line| line.chars().count())
+      .max()
+      .unwrap_or(0);
+    let pad = |line: &String| {
+      let mut chars = line.chars().collect::<Vec<_>>();
+      chars.resize(width, ' ');
+      chars
+    };
+    let rows = operand_lines.iter().map(pad).collect::<Vec<_>>();
+    let operators = pad(operator_line);
+
+    let is_blank = |col: usize| {
+      operators[col].is_whitespace() && rows.iter().all(|row| row[col].is_whitespace())
+    };
+    let mut blocks = vec![];
+    let mut col = 0;
+    while col < width {
+      if is_blank(col) {
+        col += 1;
+        continue;
+      }
+
+      let start = col;
+      while col < width && !is_blank(col) {
+        col += 1;
+      }
+      blocks.push(parse_block(input.len(), &operators, start..col)?);
+    }
+
+    Ok(Worksheet {
+      rows,
+      blocks,
+      operator_line: input.len(),
     })
-    .sum()
+  }
+
+  pub fn problems(&self, order: ReadingOrder) -> Result<Vec<MathProblem>, AocError> {
+    let by_row = matches!(order, ReadingOrder::RowWise | ReadingOrder::BottomUp);
+    let blocks = if by_row {
+      self.row_blocks()
+    } else {
+      self.blocks.clone()
+    };
+
+    blocks
+      .iter()
+      .map(|block| {
+        let (operator, column) = block.operator.ok_or_else(|| {
+          AocError::parse(
+            self.operator_line,
+            block.columns.start + 1,
+            "Problem has no operator",
+          )
+        })?;
+        let mut operands = if by_row {
+          self.row_cells(block)?
+        } else {
+          self.column_cells(block)?
+        };
+        if matches!(order, ReadingOrder::BottomUp | ReadingOrder::RightToLeft) {
+          operands.reverse();
+        }
+
+        Ok(MathProblem {
+          operands,
+          operator,
+          column,
+        })
+      })
+      .collect()
+  }
+
+  /// The blocks with each one that has no operator joined to the one on its
+  /// left.
+  fn row_blocks(&self) -> Vec<Block> {
+    let mut blocks = Vec::<Block>::new();
+    for block in &self.blocks {
+      match blocks.last_mut() {
+        Some(last) if block.operator.is_none() => last.columns.end = block.columns.end,
+        _ => blocks.push(block.clone()),
+      }
+    }
+    blocks
+  }
+
+  /// Each line of `block` as a cell. Every line must have one.
+  fn row_cells(&self, block: &Block) -> Result<Vec<Expr>, AocError> {
+    (1..)
+      .zip(&self.rows)
+      .map(|(line_no, row)| {
+        let cell = row[block.columns.clone()].iter().collect::<String>();
+        if cell.trim().is_empty() {
+          return Err(AocError::parse(
+            line_no,
+            block.columns.start + 1,
+            "Missing operand",
+          ));
+        }
+        parse_cell(&cell, |offset| (line_no, block.columns.start + offset + 1))
+      })
+      .collect()
+  }
+
+  /// Each column of `block` that isn't blank, read top to bottom, as a cell.
+  fn column_cells(&self, block: &Block) -> Result<Vec<Expr>, AocError> {
+    block
+      .columns
+      .clone()
+      .filter_map(|col| {
+        let cell = self.rows.iter().map(|row| row[col]).collect::<String>();
+        (!cell.trim().is_empty()).then(|| parse_cell(&cell, |offset| (offset + 1, col + 1)))
+      })
+      .collect()
+  }
+}
+
+/// Reads the operator in `columns` of the operator line, if there is one.
+fn parse_block(
+  line_no: usize,
+  operators: &[char],
+  columns: Range<usize>,
+) -> Result<Block, AocError> {
+  let text = operators[columns.clone()].iter().collect::<String>();
+  let tokens = shared::tokens_with_columns(&text)
+    .map(|(column, token)| (columns.start + column, token))
+    .collect::<Vec<_>>();
+
+  match tokens[..] {
+    [] => Ok(Block {
+      columns,
+      operator: None,
+    }),
+    [(column, token)] => {
+      let operator = token
+        .parse::<Operator>()
+        .map_err(|_| AocError::parse(line_no, column, format!("Unknown operator '{}'", token)))?;
+      Ok(Block {
+        columns,
+        operator: Some((operator, column)),
+      })
+    }
+    [_, (column, _), ..] => Err(AocError::parse(
+      line_no,
+      column,
+      "Problem has more than one operator",
+    )),
+  }
+}
+
+/// Sums the answers to every problem, failing if any step can't be evaluated.
+pub fn solve(input: &[MathProblem], evaluation: Evaluation) -> Result<Rational, AocError> {
+  input.iter().try_fold(Rational::ZERO, |total, problem| {
+    let answer = problem.evaluate(evaluation)?;
+    Operator::Add
+      .apply(total, answer, evaluation)
+      .map_err(|_| AocError::solve(format!("Grand total overflows {}", evaluation.precision)))
+  })
+}
+
+/// `solve` as the puzzle defines it, with integer division, in `precision`.
+fn solve_puzzle(input: &[MathProblem], precision: Precision) -> Result<i128, AocError> {
+  let evaluation = Evaluation {
+    precision,
+    ..Evaluation::default()
+  };
+  whole_total(solve(input, evaluation)?)
 }
 
-pub fn run() {
-  let input_lines = shared::load_input_lines("day6.txt").unwrap();
-  let puzzle_input1 = puzzle1::parse_input(&input_lines).unwrap();
+/// The grand total, which already fits the precision it was computed in.
+fn whole_total(total: Rational) -> Result<i128, AocError> {
+  total
+    .to_integer()
+    .ok_or_else(|| AocError::solve(format!("Grand total {} is not a whole number", total)))
+}
 
-  let result1 = solve(&puzzle_input1);
-  println!("Day 6, Puzzle 1: {}", result1);
-  let puzzle_input2 = puzzle2::parse_input(&input_lines).unwrap();
+mod puzzle1 {
+  use super::{Precision, ReadingOrder, Worksheet, solve_puzzle};
+  use crate::error::AocError;
 
-  let result2 = solve(&puzzle_input2);
-  println!("Day 6, Puzzle 2: {}", result2);
+  pub fn solve(worksheet: &Worksheet, precision: Precision) -> Result<i128, AocError> {
+    solve_puzzle(&worksheet.problems(ReadingOrder::RowWise)?, precision)
+  }
+}
+
+mod puzzle2 {
+  use super::{Precision, ReadingOrder, Worksheet, solve_puzzle};
+  use crate::error::AocError;
+
+  pub fn solve(worksheet: &Worksheet, precision: Precision) -> Result<i128, AocError> {
+    solve_puzzle(&worksheet.problems(ReadingOrder::RightToLeft)?, precision)
+  }
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+  type Input = (Worksheet, Precision);
+  type Output1 = i128;
+  type Output2 = i128;
+
+  fn parse(input: &str) -> Result<Self::Input, AocError> {
+    Day6::parse_with(input, &Options::default())
+  }
+
+  fn parse_with(input: &str, options: &Options) -> Result<Self::Input, AocError> {
+    let worksheet = Worksheet::parse(&shared::to_lines(input))?;
+    Ok((worksheet, options.precision))
+  }
+
+  fn part1(input: &Self::Input) -> Result<Self::Output1, AocError> {
+    puzzle1::solve(&input.0, input.1)
+  }
+
+  fn part2(input: &Self::Input) -> Result<Self::Output2, AocError> {
+    puzzle2::solve(&input.0, input.1)
+  }
 }
 
 #[cfg(test)]
 mod tests {
-
+  use super::*;
   use std::sync::LazyLock;
 
   static TEST_INPUT: LazyLock<Vec<String>> = LazyLock::new(|| {
@@ -167,43 +562,379 @@ mod tests {
     ]
   });
 
+  fn evaluation(precision: Precision, division: Division) -> Evaluation {
+    Evaluation {
+      precision,
+      division,
+    }
+  }
+
+  /// Evaluates a single problem with `operator` over `operands`.
+  fn evaluate(
+    operator: &str,
+    operands: &[i128],
+    evaluation: Evaluation,
+  ) -> Result<String, AocError> {
+    let mut lines = operands
+      .iter()
+      .map(|operand| operand.to_string())
+      .collect::<Vec<_>>();
+    lines.push(operator.to_string());
+    let problems = row_wise(&lines)?;
+    problems[0]
+      .evaluate(evaluation)
+      .map(|answer| answer.to_string())
+  }
+
+  fn row_wise(input: &[String]) -> Result<Vec<MathProblem>, AocError> {
+    Worksheet::parse(input)?.problems(ReadingOrder::RowWise)
+  }
+
+  fn right_to_left(input: &[String]) -> Result<Vec<MathProblem>, AocError> {
+    Worksheet::parse(input)?.problems(ReadingOrder::RightToLeft)
+  }
+
+  fn numbers(values: &[i128]) -> Vec<Expr> {
+    values.iter().map(|value| Expr::Number(*value)).collect()
+  }
+
+  #[test]
+  fn test_expression_cells() {
+    let input = vec![
+      "2*(3+4) 1".to_string(),
+      "-5      1".to_string(),
+      "+       -".to_string(),
+    ];
+    let problems = row_wise(&input).unwrap();
+    assert_eq!(solve_puzzle(&problems, Precision::I64).unwrap(), 9);
+
+    // Read top to bottom, the first problem's columns are `1+2` and `(3)*4`.
+    let input = vec![
+      "1( 6".to_string(),
+      "+3 /".to_string(),
+      "2) 2".to_string(),
+      " *".to_string(),
+      " 4".to_string(),
+      "+  *".to_string(),
+    ];
+    let problems = right_to_left(&input).unwrap();
+    assert_eq!(
+      solve_puzzle(&problems, Precision::I64).unwrap(),
+      (3 + 12) + 3
+    );
+
+    let mut bad_cell = input.clone();
+    bad_cell[3] = " * %".to_string();
+    assert!(matches!(
+      right_to_left(&bad_cell),
+      Err(AocError::Parse {
+        line: 4,
+        column: 4,
+        ..
+      })
+    ));
+    assert!(matches!(
+      row_wise(&["1 2*(3".to_string(), "+ +".to_string()]),
+      Err(AocError::Parse {
+        line: 1,
+        column: 7,
+        ..
+      })
+    ));
+
+    let input = vec!["4/(2-2)".to_string(), "3".to_string(), "+".to_string()];
+    let problems = row_wise(&input).unwrap();
+    assert_eq!(
+      solve_puzzle(&problems, Precision::I64)
+        .unwrap_err()
+        .to_string(),
+      "Problem at column 1 divides by zero: 4 / 0"
+    );
+  }
+
+  #[test]
+  fn test_reading_orders() {
+    let worksheet = Worksheet::parse(&TEST_INPUT).unwrap();
+    let first_operands = |order| worksheet.problems(order).unwrap()[0].operands.clone();
+    assert_eq!(
+      first_operands(ReadingOrder::RowWise),
+      numbers(&[123, 45, 6])
+    );
+    assert_eq!(
+      first_operands(ReadingOrder::BottomUp),
+      numbers(&[6, 45, 123])
+    );
+    assert_eq!(
+      first_operands(ReadingOrder::ColumnWise),
+      numbers(&[1, 24, 356])
+    );
+    assert_eq!(
+      first_operands(ReadingOrder::RightToLeft),
+      numbers(&[356, 24, 1])
+    );
+
+    // Order only matters for `-` and `/`.
+    let input = vec!["20".to_string(), " 5".to_string(), "- ".to_string()];
+    let worksheet = Worksheet::parse(&input).unwrap();
+    let answer = |order| solve_puzzle(&worksheet.problems(order).unwrap(), Precision::I64).unwrap();
+    assert_eq!(answer(ReadingOrder::RowWise), 15);
+    assert_eq!(answer(ReadingOrder::BottomUp), -15);
+    assert_eq!(answer(ReadingOrder::ColumnWise), 2 - 5);
+    assert_eq!(answer(ReadingOrder::RightToLeft), 5 - 2);
+  }
+
+  #[test]
+  fn test_worksheet_layout() {
+    // Trailing spaces trimmed, and problems separated by several blank
+    // columns.
+    let ragged = vec![
+      "123 328    51 64".to_string(),
+      " 45 64    387 23".to_string(),
+      "  6 98    215 314".to_string(),
+      "*   +     *   +".to_string(),
+    ];
+    assert_eq!(
+      solve_puzzle(&row_wise(&ragged).unwrap(), Precision::I64).unwrap(),
+      4277556
+    );
+    assert_eq!(
+      solve_puzzle(&right_to_left(&ragged).unwrap(), Precision::I64).unwrap(),
+      3263827
+    );
+
+    // Row-wise cells can hold expressions with spaces in them.
+    let spaced = vec![
+      "1 + 2  10".to_string(),
+      "4      5".to_string(),
+      "*      +".to_string(),
+    ];
+    let problems = row_wise(&spaced).unwrap();
+    assert_eq!(problems.len(), 2);
+    assert_eq!(
+      solve_puzzle(&problems, Precision::I64).unwrap(),
+      (1 + 2) * 4 + 15
+    );
+
+    // Read by column, `2` is a problem without an operator. Read by row, it
+    // is part of the cell `1 2`, which isn't an expression.
+    let no_operator = vec!["1 2".to_string(), "+".to_string()];
+    assert!(matches!(
+      right_to_left(&no_operator),
+      Err(AocError::Parse {
+        line: 2,
+        column: 3,
+        ..
+      })
+    ));
+    assert!(matches!(
+      row_wise(&no_operator),
+      Err(AocError::Parse {
+        line: 1,
+        column: 3,
+        ..
+      })
+    ));
+    let no_operators = vec!["1".to_string(), " ".to_string()];
+    assert!(matches!(
+      row_wise(&no_operators),
+      Err(AocError::Parse {
+        line: 2,
+        column: 1,
+        ..
+      })
+    ));
+    let two_operators = vec!["123".to_string(), "+ *".to_string()];
+    assert!(matches!(
+      Worksheet::parse(&two_operators),
+      Err(AocError::Parse {
+        line: 2,
+        column: 3,
+        ..
+      })
+    ));
+  }
+
+  #[test]
+  fn test_operators() {
+    let default = Evaluation::default();
+    assert_eq!(evaluate("+", &[10, 3, 4], default).unwrap(), "17");
+    assert_eq!(evaluate("*", &[10, 3, 4], default).unwrap(), "120");
+    assert_eq!(evaluate("-", &[10, 3], default).unwrap(), "7");
+    assert_eq!(evaluate("-", &[10, 3, 4], default).unwrap(), "3");
+    assert_eq!(evaluate("-", &[-10], default).unwrap(), "-10");
+    assert_eq!(evaluate("/", &[10, 3], default).unwrap(), "3");
+    assert_eq!(evaluate("/", &[100, 3, 2], default).unwrap(), "16");
+    assert_eq!(evaluate("/", &[-7, 2], default).unwrap(), "-3");
+
+    let rational = evaluation(Precision::I64, Division::Rational);
+    assert_eq!(evaluate("/", &[10, 3], rational).unwrap(), "10/3");
+    assert_eq!(evaluate("/", &[100, 3, 2], rational).unwrap(), "50/3");
+    assert_eq!(evaluate("/", &[-6, 4], rational).unwrap(), "-3/2");
+    assert_eq!(evaluate("/", &[12, -4], rational).unwrap(), "-3");
+
+    for division in [Division::Integer, Division::Rational] {
+      let error = evaluate("/", &[10, 0, 2], evaluation(Precision::I64, division)).unwrap_err();
+      assert_eq!(
+        error.to_string(),
+        "Problem at column 1 divides by zero: 10 / 0"
+      );
+    }
+  }
+
+  #[test]
+  fn test_rational() {
+    let half = Rational::new(1, 2).unwrap();
+    let third = Rational::new(-2, -6).unwrap();
+    assert_eq!((third.numerator(), third.denominator()), (1, 3));
+    assert_eq!(half.checked_add(third), Rational::new(5, 6));
+    assert_eq!(half.checked_sub(third), Rational::new(1, 6));
+    assert_eq!(half.checked_mul(third), Rational::new(1, 6));
+    assert_eq!(half.checked_div(third), Rational::new(3, 2));
+    assert_eq!(Rational::new(1, 0), None);
+    assert_eq!(half.checked_div(Rational::ZERO), None);
+    assert_eq!(Rational::integer(i128::MIN).checked_neg(), None);
+
+    // Rational totals only need to be whole in the integer mode.
+    let input = vec!["1 1".to_string(), "2 3".to_string(), "/ /".to_string()];
+    let problems = row_wise(&input).unwrap();
+    let total = solve(&problems, evaluation(Precision::I64, Division::Rational)).unwrap();
+    assert_eq!(total, Rational::new(5, 6).unwrap());
+    assert_eq!(
+      solve(&problems, Evaluation::default()).unwrap(),
+      Rational::ZERO
+    );
+
+    assert_eq!(whole_total(Rational::integer(-42)).unwrap(), -42);
+    assert_eq!(
+      whole_total(total).unwrap_err().to_string(),
+      "Grand total 5/6 is not a whole number"
+    );
+  }
+
   mod puzzle1 {
-    use super::super::puzzle1::*;
-    use super::super::*;
+    use super::super::puzzle1::solve;
     use super::*;
 
     #[test]
     fn test_parse_input() {
-      let parsed = parse_input(&TEST_INPUT).unwrap();
+      let parsed = row_wise(&TEST_INPUT).unwrap();
       assert_eq!(parsed.len(), 4);
-      assert_eq!(parsed[0].operands, vec![123, 45, 6]);
-      assert_eq!(parsed[1].operands, vec![328, 64, 98]);
+      assert_eq!(parsed[0].operands, numbers(&[123, 45, 6]));
+      assert_eq!(parsed[1].operands, numbers(&[328, 64, 98]));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+      assert!(row_wise(&[]).is_err());
+
+      let mut bad_operator = TEST_INPUT.clone();
+      bad_operator[3] = "*   +   %   +  ".to_string();
+      assert!(matches!(
+        row_wise(&bad_operator),
+        Err(AocError::Parse {
+          line: 4,
+          column: 9,
+          ..
+        })
+      ));
+
+      let mut short_row = TEST_INPUT.clone();
+      short_row[1] = " 45 64  387".to_string();
+      assert!(matches!(
+        row_wise(&short_row),
+        Err(AocError::Parse {
+          line: 2,
+          column: 13,
+          ..
+        })
+      ));
     }
 
     #[test]
     fn test_solve() {
-      let parsed = parse_input(&TEST_INPUT).unwrap();
-      assert_eq!(solve(&parsed), 4277556);
+      let worksheet = Worksheet::parse(&TEST_INPUT).unwrap();
+      assert_eq!(solve(&worksheet, Precision::I64).unwrap(), 4277556);
+    }
+
+    #[test]
+    fn test_overflow() {
+      let input = vec![
+        "9223372036854775807 3".to_string(),
+        "                  2 4".to_string(),
+        "+                   *".to_string(),
+      ];
+      let worksheet = Worksheet::parse(&input).unwrap();
+      let error = solve(&worksheet, Precision::I64).unwrap_err();
+      assert_eq!(
+        error.to_string(),
+        "Problem at column 1 overflows i64: 9223372036854775807 + 2"
+      );
+      assert_eq!(
+        solve(&worksheet, Precision::I128).unwrap(),
+        9223372036854775809 + 12
+      );
+
+      // Problems that fit can still add up to a total that doesn't.
+      let total = vec![
+        "9223372036854775807 1".to_string(),
+        "+                   +".to_string(),
+      ];
+      assert_eq!(
+        solve(&Worksheet::parse(&total).unwrap(), Precision::I64)
+          .unwrap_err()
+          .to_string(),
+        "Grand total overflows i64"
+      );
+
+      let huge = [
+        "9999999999999999999".to_string(),
+        "9999999999999999999".to_string(),
+        "*".to_string(),
+      ];
+      let options = Options {
+        precision: Precision::I128,
+      };
+      let input = Day6::parse_with(&huge.join("\n"), &options).unwrap();
+      assert_eq!(
+        Day6::part1(&input).unwrap(),
+        9999999999999999999 * 9999999999999999999
+      );
+      let input = Day6::parse(&huge.join("\n")).unwrap();
+      assert!(Day6::part1(&input).is_err());
     }
   }
 
   mod puzzle2 {
-    use super::super::puzzle2::*;
-    use super::super::*;
+    use super::super::puzzle2::solve;
     use super::*;
 
     #[test]
     fn test_parse_input() {
-      let parsed = parse_input(&TEST_INPUT).unwrap();
+      let parsed = right_to_left(&TEST_INPUT).unwrap();
       assert_eq!(parsed.len(), 4);
-      assert_eq!(parsed[0].operands, vec![1, 24, 356]);
-      assert_eq!(parsed[1].operands, vec![369, 248, 8]);
+      assert_eq!(parsed[0].operands, numbers(&[356, 24, 1]));
+      assert_eq!(parsed[1].operands, numbers(&[8, 248, 369]));
     }
 
     #[test]
     fn test_solve() {
-      let parsed = parse_input(&TEST_INPUT).unwrap();
-      assert_eq!(solve(&parsed), 3263827);
+      let worksheet = Worksheet::parse(&TEST_INPUT).unwrap();
+      assert_eq!(solve(&worksheet, Precision::I64).unwrap(), 3263827);
+    }
+
+    #[test]
+    fn test_solve_reads_right_to_left() {
+      // The columns are 1 and 23, then 8 and 42.
+      let input = vec![
+        "12 84".to_string(),
+        " 3  2".to_string(),
+        "-  / ".to_string(),
+      ];
+      let worksheet = Worksheet::parse(&input).unwrap();
+      assert_eq!(
+        solve(&worksheet, Precision::I64).unwrap(),
+        (23 - 1) + 42 / 8
+      );
     }
   }
 }
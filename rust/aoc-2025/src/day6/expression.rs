@@ -0,0 +1,393 @@
+use super::{ArithmeticError, Evaluation, Operator, Rational};
+
+/// An arithmetic expression in a worksheet cell, such as `-(1+2)*3`. `*` and
+/// `/` bind tighter than `+` and `-`, and all four are left associative.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+  Number(i128),
+  Negate(Box<Expr>),
+  Binary(Operator, Box<Expr>, Box<Expr>),
+}
+
+/// A syntax error `offset` characters into the parsed text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+  pub offset: usize,
+  pub message: String,
+}
+
+/// The step an evaluation failed at, e.g. `10 / 0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalError {
+  pub error: ArithmeticError,
+  pub step: String,
+}
+
+impl Expr {
+  pub fn evaluate(&self, evaluation: Evaluation) -> Result<Rational, EvalError> {
+    let fits = |value: Rational| evaluation.precision.fits(value).then_some(value);
+    match self {
+      Expr::Number(value) => fits(Rational::integer(*value)).ok_or_else(|| EvalError {
+        error: ArithmeticError::Overflow,
+        step: value.to_string(),
+      }),
+      Expr::Negate(operand) => {
+        let value = operand.evaluate(evaluation)?;
+        value.checked_neg().and_then(fits).ok_or_else(|| EvalError {
+          error: ArithmeticError::Overflow,
+          step: format!("-({})", value),
+        })
+      }
+      Expr::Binary(operator, lhs, rhs) => {
+        let (lhs, rhs) = (lhs.evaluate(evaluation)?, rhs.evaluate(evaluation)?);
+        operator
+          .apply(lhs, rhs, evaluation)
+          .map_err(|error| EvalError {
+            error,
+            step: format!("{} {} {}", lhs, operator, rhs),
+          })
+      }
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+  /// Unsigned, so that `-` can be applied to `i128::MIN`'s magnitude.
+  Number(u128),
+  Operator(Operator),
+  Open,
+  Close,
+}
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, SyntaxError> {
+  let chars = text.chars().collect::<Vec<_>>();
+  let mut tokens = vec![];
+  let mut offset = 0;
+  while offset < chars.len() {
+    let char = chars[offset];
+    let token = match char {
+      _ if char.is_whitespace() => {
+        offset += 1;
+        continue;
+      }
+      '0'..='9' => {
+        let end = (offset..chars.len())
+          .find(|i| !chars[*i].is_ascii_digit())
+          .unwrap_or(chars.len());
+        let digits = chars[offset..end].iter().collect::<String>();
+        let value = digits.parse::<u128>().map_err(|e| SyntaxError {
+          offset,
+          message: format!("Invalid number '{}': {}", digits, e),
+        })?;
+        tokens.push((offset, Token::Number(value)));
+        offset = end;
+        continue;
+      }
+      '(' => Token::Open,
+      ')' => Token::Close,
+      _ => match char.to_string().parse::<Operator>() {
+        Ok(operator) => Token::Operator(operator),
+        Err(_) => {
+          return Err(SyntaxError {
+            offset,
+            message: format!("Unexpected character '{}'", char),
+          });
+        }
+      },
+    };
+    tokens.push((offset, token));
+    offset += 1;
+  }
+
+  Ok(tokens)
+}
+
+/// How deeply parentheses, unary operators and chains of binary operators
+/// may nest. Parsing, evaluating and dropping an `Expr` all recurse, so
+/// anything deeper is an error rather than a stack overflow.
+const MAX_DEPTH: usize = 256;
+
+/// An expression and the height of its tree.
+type Parsed = Result<(Expr, usize), SyntaxError>;
+
+/// A recursive descent parser over the grammar
+///
+/// ```text
+/// sum     = product (("+" | "-") product)*
+/// product = unary (("*" | "/") unary)*
+/// unary   = ("-" | "+") unary | primary
+/// primary = number | "(" sum ")"
+/// ```
+struct Parser {
+  tokens: Vec<(usize, Token)>,
+  position: usize,
+  /// Where errors at the end of the text are reported.
+  end: usize,
+  depth: usize,
+}
+
+impl Parser {
+  fn peek(&self) -> Option<Token> {
+    self.tokens.get(self.position).map(|(_, token)| *token)
+  }
+
+  fn offset(&self) -> usize {
+    self
+      .tokens
+      .get(self.position)
+      .map_or(self.end, |(offset, _)| *offset)
+  }
+
+  fn error(&self, message: &str) -> SyntaxError {
+    SyntaxError {
+      offset: self.offset(),
+      message: message.to_string(),
+    }
+  }
+
+  /// Runs `parse` one level deeper, after consuming the current token.
+  fn nested(&mut self, parse: fn(&mut Parser) -> Parsed) -> Parsed {
+    if self.depth == MAX_DEPTH {
+      return Err(self.error("Expression is nested too deeply"));
+    }
+    self.position += 1;
+    self.depth += 1;
+    let expr = parse(self);
+    self.depth -= 1;
+    expr
+  }
+
+  /// A left-deep chain of `operators`, each of which adds a level to the
+  /// tree.
+  fn binary(&mut self, operators: [Operator; 2], operand: fn(&mut Parser) -> Parsed) -> Parsed {
+    let (mut expr, mut height) = operand(self)?;
+    while let Some(Token::Operator(operator)) = self.peek()
+      && operators.contains(&operator)
+    {
+      let error = self.error("Expression has too many operators");
+      self.position += 1;
+      let (rhs, rhs_height) = operand(self)?;
+      height = height.max(rhs_height) + 1;
+      if height > MAX_DEPTH {
+        return Err(error);
+      }
+      expr = Expr::Binary(operator, Box::new(expr), Box::new(rhs));
+    }
+    Ok((expr, height))
+  }
+
+  fn sum(&mut self) -> Parsed {
+    self.binary([Operator::Add, Operator::Subtract], Parser::product)
+  }
+
+  fn product(&mut self) -> Parsed {
+    self.binary([Operator::Multiply, Operator::Divide], Parser::unary)
+  }
+
+  fn unary(&mut self) -> Parsed {
+    match self.peek() {
+      Some(Token::Operator(Operator::Subtract)) => {
+        // The one literal whose negation fits but whose magnitude doesn't.
+        let magnitude = i128::MIN.unsigned_abs();
+        if let Some((_, Token::Number(value))) = self.tokens.get(self.position + 1)
+          && *value == magnitude
+        {
+          self.position += 2;
+          return Ok((Expr::Number(i128::MIN), 0));
+        }
+        let (operand, height) = self.nested(Parser::unary)?;
+        Ok((Expr::Negate(Box::new(operand)), height + 1))
+      }
+      Some(Token::Operator(Operator::Add)) => self.nested(Parser::unary),
+      _ => self.primary(),
+    }
+  }
+
+  fn primary(&mut self) -> Parsed {
+    match self.peek() {
+      Some(Token::Number(value)) => {
+        let value = i128::try_from(value)
+          .map_err(|_| self.error(&format!("Number '{}' is too large", value)))?;
+        self.position += 1;
+        Ok((Expr::Number(value), 0))
+      }
+      Some(Token::Open) => {
+        let expr = self.nested(Parser::sum)?;
+        if self.peek() != Some(Token::Close) {
+          return Err(self.error("Expected ')'"));
+        }
+        self.position += 1;
+        Ok(expr)
+      }
+      Some(_) => Err(self.error("Expected a number or '('")),
+      None => Err(self.error("Unexpected end of expression")),
+    }
+  }
+}
+
+pub fn parse(text: &str) -> Result<Expr, SyntaxError> {
+  let mut parser = Parser {
+    tokens: tokenize(text)?,
+    position: 0,
+    end: text.chars().count(),
+    depth: 0,
+  };
+
+  let (expr, _) = parser.sum()?;
+  if parser.peek().is_some() {
+    return Err(parser.error("Expected an operator"));
+  }
+  Ok(expr)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::day6::{Division, Precision};
+
+  fn number(value: i128) -> Box<Expr> {
+    Box::new(Expr::Number(value))
+  }
+
+  fn eval(text: &str) -> Result<String, EvalError> {
+    let evaluation = Evaluation {
+      precision: Precision::I64,
+      division: Division::Rational,
+    };
+    parse(text)
+      .unwrap()
+      .evaluate(evaluation)
+      .map(|value| value.to_string())
+  }
+
+  #[test]
+  fn test_parse() {
+    assert_eq!(parse(" 42 ").unwrap(), Expr::Number(42));
+    assert_eq!(
+      parse("1+2*3").unwrap(),
+      Expr::Binary(
+        Operator::Add,
+        number(1),
+        Box::new(Expr::Binary(Operator::Multiply, number(2), number(3)))
+      )
+    );
+    assert_eq!(
+      parse("-(1-2)-3").unwrap(),
+      Expr::Binary(
+        Operator::Subtract,
+        Box::new(Expr::Negate(Box::new(Expr::Binary(
+          Operator::Subtract,
+          number(1),
+          number(2)
+        )))),
+        number(3)
+      )
+    );
+  }
+
+  #[test]
+  fn test_syntax_errors() {
+    let offset = |text| parse(text).unwrap_err().offset;
+    assert_eq!(offset("1+"), 2);
+    assert_eq!(offset("(1+2"), 4);
+    assert_eq!(offset("1 2"), 2);
+    assert_eq!(offset("1+*2"), 2);
+    assert_eq!(offset("3%2"), 1);
+    assert_eq!(offset(""), 0);
+    assert_eq!(offset("1+)"), 2);
+    assert_eq!(offset("2*170141183460469231731687303715884105728"), 2);
+
+    let deep = format!(
+      "{}1{}",
+      "(".repeat(MAX_DEPTH + 1),
+      ")".repeat(MAX_DEPTH + 1)
+    );
+    assert_eq!(offset(&deep), MAX_DEPTH);
+    assert!(
+      parse(&format!(
+        "{}1{}",
+        "(".repeat(MAX_DEPTH),
+        ")".repeat(MAX_DEPTH)
+      ))
+      .is_ok()
+    );
+    assert_eq!(
+      offset(&format!("{}1", "-".repeat(MAX_DEPTH + 1))),
+      MAX_DEPTH
+    );
+  }
+
+  #[test]
+  fn test_long_chains() {
+    let chain = |terms: usize, operator: &str| vec!["1"; terms].join(operator);
+    assert_eq!(
+      eval(&chain(MAX_DEPTH + 1, "+")).unwrap(),
+      (MAX_DEPTH + 1).to_string()
+    );
+    assert_eq!(eval(&chain(MAX_DEPTH + 1, "*")).unwrap(), "1");
+
+    // The error is at the operator that makes the tree too tall.
+    let error = parse(&chain(200_000, "+")).unwrap_err();
+    assert_eq!(error.offset, 2 * MAX_DEPTH + 1);
+    assert_eq!(error.message, "Expression has too many operators");
+    assert!(parse(&chain(200_000, "*")).is_err());
+    assert!(parse(&format!("({})", chain(200_000, "-"))).is_err());
+
+    // Nesting and chains add up.
+    let nested = format!(
+      "{}{}",
+      "-".repeat(MAX_DEPTH / 2),
+      chain(MAX_DEPTH / 2 + 2, "/")
+    );
+    assert!(parse(&nested).is_err());
+    let nested = format!(
+      "{}{}",
+      "-".repeat(MAX_DEPTH / 2),
+      chain(MAX_DEPTH / 2 + 1, "/")
+    );
+    assert_eq!(eval(&nested).unwrap(), "1");
+  }
+
+  #[test]
+  fn test_signed_literals() {
+    assert_eq!(parse("+5").unwrap(), Expr::Number(5));
+    assert_eq!(parse("3 - +2").unwrap(), parse("3 - 2").unwrap());
+    assert_eq!(
+      parse("-170141183460469231731687303715884105728").unwrap(),
+      Expr::Number(i128::MIN)
+    );
+    assert_eq!(
+      parse("--170141183460469231731687303715884105728").unwrap(),
+      Expr::Negate(Box::new(Expr::Number(i128::MIN)))
+    );
+  }
+
+  #[test]
+  fn test_evaluate() {
+    assert_eq!(eval("1+2*3").unwrap(), "7");
+    assert_eq!(eval("(1+2)*3").unwrap(), "9");
+    assert_eq!(eval("10-3-4").unwrap(), "3");
+    assert_eq!(eval("100/10/5").unwrap(), "2");
+    assert_eq!(eval("--5").unwrap(), "5");
+    assert_eq!(eval("+5-+2").unwrap(), "3");
+    assert_eq!(eval("2*-(3+4)").unwrap(), "-14");
+    assert_eq!(eval("1/3+1/6").unwrap(), "1/2");
+
+    assert_eq!(
+      eval("1+4/(2-2)").unwrap_err(),
+      EvalError {
+        error: ArithmeticError::DivisionByZero,
+        step: "4 / 0".to_string(),
+      }
+    );
+    assert_eq!(
+      eval("-9223372036854775807-2").unwrap_err().error,
+      ArithmeticError::Overflow
+    );
+    assert_eq!(
+      eval("9223372036854775808").unwrap_err().error,
+      ArithmeticError::Overflow
+    );
+  }
+}
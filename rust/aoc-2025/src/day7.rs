@@ -1,74 +1,374 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::error::AocError;
 use crate::shared;
+use crate::shared::grid::{Coord, Grid};
+use crate::solution::Solution;
 
-mod puzzle1 {
-  use std::collections::HashSet;
-  pub fn solve(input: &Vec<String>) -> i32 {
-    let mut beams = HashSet::new();
-    let mut count = 0;
-    for line in input {
-      let chars = line.chars().collect::<Vec<char>>();
-      for col in 0..line.len() {
-        let c = chars[col];
-        if c == 'S' {
-          beams.insert(col);
-        }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+  Up,
+  Down,
+  Left,
+  Right,
+}
+
+impl Direction {
+  fn offset(self) -> (isize, isize) {
+    match self {
+      Direction::Up => (0, -1),
+      Direction::Down => (0, 1),
+      Direction::Left => (-1, 0),
+      Direction::Right => (1, 0),
+    }
+  }
+
+  fn is_vertical(self) -> bool {
+    matches!(self, Direction::Up | Direction::Down)
+  }
+}
+
+/// Where a beam goes after entering a cell: it moves `shift` from the cell
+/// without that cell acting on it, then carries on `heading`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Exit {
+  pub shift: (isize, isize),
+  pub heading: Direction,
+}
+
+impl Exit {
+  const fn new(dx: isize, heading: Direction) -> Self {
+    Exit {
+      shift: (dx, 0),
+      heading,
+    }
+  }
+}
 
-        if c == '^' {
-          if beams.contains(&col) {
-            beams.remove(&col);
-            beams.insert(col - 1);
-            beams.insert(col + 1);
-            count += 1;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+  Empty,
+  /// A source, which sends one beam down. It lets other beams through.
+  Start,
+  /// Sends a falling beam out of both sides, where each falls on.
+  Splitter,
+  /// A splitter that only sends a falling beam out of its left side.
+  LeftSplitter,
+  /// A splitter that only sends a falling beam out of its right side.
+  RightSplitter,
+  /// `/`, turning beams by 90 degrees.
+  Mirror,
+  /// `\`, turning beams by 90 degrees.
+  BackMirror,
+  /// Stops every beam.
+  Absorber,
+}
+
+impl Cell {
+  /// Every cell with its symbol. A new kind of cell needs a variant, a row
+  /// here and its behaviour in `exits`; symbols and splitter counts follow
+  /// from those.
+  pub const TABLE: [(char, Cell); 8] = [
+    ('.', Cell::Empty),
+    ('S', Cell::Start),
+    ('^', Cell::Splitter),
+    ('<', Cell::LeftSplitter),
+    ('>', Cell::RightSplitter),
+    ('/', Cell::Mirror),
+    ('\\', Cell::BackMirror),
+    ('#', Cell::Absorber),
+  ];
+
+  fn from_symbol(symbol: char) -> Option<Cell> {
+    Cell::TABLE
+      .iter()
+      .find(|(s, _)| *s == symbol)
+      .map(|(_, cell)| *cell)
+  }
+
+  fn symbol(self) -> char {
+    Cell::TABLE
+      .iter()
+      .find(|(_, cell)| *cell == self)
+      .map_or('?', |(symbol, _)| *symbol)
+  }
+
+  /// Whether the cell moves a falling beam sideways.
+  fn is_splitter(self) -> bool {
+    self
+      .exits(Direction::Down)
+      .iter()
+      .any(|exit| exit.shift.0 != 0)
+  }
+
+  /// Where a beam entering this cell heading `heading` goes. Splitters only
+  /// act on falling beams and let others pass.
+  pub fn exits(self, heading: Direction) -> Vec<Exit> {
+    use Direction::*;
+    let pass = Exit::new(0, heading);
+    match (self, heading) {
+      (Cell::Empty | Cell::Start, _) => vec![pass],
+      (Cell::Splitter, Down) => vec![Exit::new(-1, Down), Exit::new(1, Down)],
+      (Cell::LeftSplitter, Down) => vec![Exit::new(-1, Down)],
+      (Cell::RightSplitter, Down) => vec![Exit::new(1, Down)],
+      (Cell::Splitter | Cell::LeftSplitter | Cell::RightSplitter, _) => vec![pass],
+      (Cell::Mirror, _) => vec![Exit::new(
+        0,
+        match heading {
+          Up => Right,
+          Right => Up,
+          Down => Left,
+          Left => Down,
+        },
+      )],
+      (Cell::BackMirror, _) => vec![Exit::new(
+        0,
+        match heading {
+          Up => Left,
+          Left => Up,
+          Down => Right,
+          Right => Down,
+        },
+      )],
+      (Cell::Absorber, _) => vec![],
+    }
+  }
+}
+
+fn parse_manifold(input: &[String]) -> Result<Grid<Cell>, AocError> {
+  Grid::parse(input, Cell::from_symbol)
+}
+
+/// A beam sitting in a cell, about to move `heading`.
+type Beam = (Coord, Direction);
+
+/// What becomes of a beam after its next move.
+enum Step {
+  Beam(Beam),
+  Leaves,
+  Absorbed,
+}
+
+/// Moves `beam` into the next cell and lets that cell act on it.
+fn step(grid: &Grid<Cell>, (coord, heading): Beam) -> (Option<Coord>, Vec<Step>) {
+  let (dx, dy) = heading.offset();
+  let Some(entered) = coord.offset(dx, dy).filter(|coord| grid.in_bounds(*coord)) else {
+    return (None, vec![Step::Leaves]);
+  };
+
+  let exits = grid[entered].exits(heading);
+  if exits.is_empty() {
+    return (Some(entered), vec![Step::Absorbed]);
+  }
+
+  let steps = exits
+    .into_iter()
+    .map(|exit| {
+      match entered
+        .offset(exit.shift.0, exit.shift.1)
+        .filter(|coord| grid.in_bounds(*coord))
+      {
+        Some(coord) => Step::Beam((coord, exit.heading)),
+        None => Step::Leaves,
+      }
+    })
+    .collect();
+  (Some(entered), steps)
+}
+
+/// The beams in a row, by column, with how many timelines pass through each.
+pub type Beams = BTreeMap<usize, u64>;
+
+/// Everything that happened to the beams on their way through a manifold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simulation {
+  /// The splitters at least one falling beam reached.
+  pub splitters_hit: BTreeSet<Coord>,
+  /// The beams in each row.
+  pub rows: Vec<Beams>,
+  /// Where beams passed through each cell, to draw them.
+  headings: BTreeMap<Coord, BTreeSet<Direction>>,
+  /// Timelines that left the manifold, through any edge.
+  pub left: u64,
+  /// Timelines that ended in an absorber.
+  pub absorbed: u64,
+}
+
+/// Sends a beam down from every `S` and follows it through every cell. Every
+/// way a beam can go from a source to leaving the manifold or being absorbed
+/// is a separate timeline. Fails if a beam can go round in a loop, since it
+/// would then have endless timelines.
+pub fn simulate(grid: &Grid<Cell>) -> Result<Simulation, AocError> {
+  let sources = grid
+    .coords()
+    .filter(|coord| grid[*coord] == Cell::Start)
+    .map(|coord| (coord, Direction::Down))
+    .collect::<Vec<_>>();
+
+  // Depth-first search for every reachable beam, finishing each after all
+  // the beams it leads to; meeting a beam that is still on the stack means a
+  // loop.
+  let mut successors = HashMap::new();
+  let mut splitters_hit = BTreeSet::new();
+  let mut expand = |beam: Beam| {
+    let (entered, steps) = step(grid, beam);
+    if let Some(entered) = entered
+      && grid[entered].is_splitter()
+      && beam.1 == Direction::Down
+    {
+      splitters_hit.insert(entered);
+    }
+
+    let children = steps
+      .iter()
+      .filter_map(|step| match step {
+        Step::Beam(child) => Some(*child),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    successors.insert(beam, steps);
+    children
+  };
+
+  let mut visited = BTreeSet::new();
+  let mut on_stack = BTreeSet::new();
+  let mut order = vec![];
+  for source in &sources {
+    if !visited.insert(*source) {
+      continue;
+    }
+
+    on_stack.insert(*source);
+    let mut stack = vec![(*source, expand(*source))];
+    while let Some((beam, children)) = stack.last_mut() {
+      match children.pop() {
+        None => {
+          on_stack.remove(beam);
+          order.push(*beam);
+          stack.pop();
+        }
+        Some(child) if on_stack.contains(&child) => {
+          return Err(AocError::solve(format!(
+            "A beam loops forever through line {}, column {}",
+            child.0.y + 1,
+            child.0.x + 1
+          )));
+        }
+        Some(child) => {
+          if visited.insert(child) {
+            on_stack.insert(child);
+            stack.push((child, expand(child)));
           }
         }
       }
     }
+  }
+
+  // Push timelines forwards in topological order.
+  let overflow = || AocError::solve("Too many timelines to count");
+  let mut timelines = HashMap::<Beam, u64>::new();
+  for source in &sources {
+    *timelines.entry(*source).or_default() += 1;
+  }
+  let mut rows = vec![Beams::new(); grid.height()];
+  let mut headings = BTreeMap::<Coord, BTreeSet<Direction>>::new();
+  let (mut left, mut absorbed) = (0u64, 0u64);
+  for beam in order.iter().rev() {
+    let count = timelines.get(beam).copied().unwrap_or(0);
+    let (coord, heading) = *beam;
+    let through = rows[coord.y].entry(coord.x).or_default();
+    *through = through.checked_add(count).ok_or_else(overflow)?;
+    headings.entry(coord).or_default().insert(heading);
 
-    count
+    for step in &successors[beam] {
+      let total = match step {
+        Step::Beam(child) => timelines.entry(*child).or_default(),
+        Step::Leaves => &mut left,
+        Step::Absorbed => &mut absorbed,
+      };
+      *total = total.checked_add(count).ok_or_else(overflow)?;
+    }
   }
+
+  Ok(Simulation {
+    splitters_hit,
+    rows,
+    headings,
+    left,
+    absorbed,
+  })
 }
 
-mod puzzle2 {
-  use std::collections::HashMap;
-
-  fn add_beams(beams: &mut HashMap<usize, i64>, col: usize, value: i64) {
-    let _ = *beams
-      .entry(col)
-      .and_modify(|counter| *counter += value)
-      .or_insert(value);
-  }
-
-  pub fn solve(input: &Vec<String>) -> i64 {
-    let mut beams = HashMap::new();
-    for line in input {
-      let chars = line.chars().collect::<Vec<char>>();
-      for col in 0..line.len() {
-        let c = chars[col];
-        if c == 'S' {
-          beams.insert(col, 1);
-        }
+impl Simulation {
+  /// The columns beams pass through in row `y`, in order.
+  pub fn columns(&self, y: usize) -> impl Iterator<Item = usize> + '_ {
+    self.rows[y].keys().copied()
+  }
 
-        if c == '^' {
-          if let Some(timelines_at_this_splitter) = beams.remove(&col) {
-            add_beams(&mut beams, col - 1, timelines_at_this_splitter);
-            add_beams(&mut beams, col + 1, timelines_at_this_splitter);
-          }
-        }
+  /// Every timeline, however it ended.
+  pub fn timelines(&self) -> u64 {
+    self.left + self.absorbed
+  }
+
+  /// Draws `grid` with beams on the empty cells they pass through: `|` for
+  /// vertical beams, `-` for horizontal ones and `+` where they cross.
+  pub fn annotate(&self, grid: &Grid<Cell>) -> String {
+    let mut annotated = grid.map(|cell| cell.symbol());
+    for (coord, headings) in &self.headings {
+      if grid[*coord] != Cell::Empty {
+        continue;
       }
+
+      let vertical = headings.iter().any(|heading| heading.is_vertical());
+      let horizontal = headings.iter().any(|heading| !heading.is_vertical());
+      annotated[*coord] = match (vertical, horizontal) {
+        (true, true) => '+',
+        (false, true) => '-',
+        _ => '|',
+      };
     }
+    annotated.render(|symbol| *symbol)
+  }
+}
+
+mod puzzle1 {
+  use super::{Cell, simulate};
+  use crate::error::AocError;
+  use crate::shared::grid::Grid;
+
+  pub fn solve(input: &Grid<Cell>) -> Result<usize, AocError> {
+    Ok(simulate(input)?.splitters_hit.len())
+  }
+}
+
+mod puzzle2 {
+  use super::{Cell, simulate};
+  use crate::error::AocError;
+  use crate::shared::grid::Grid;
 
-    beams.values().sum()
+  pub fn solve(input: &Grid<Cell>) -> Result<u64, AocError> {
+    Ok(simulate(input)?.timelines())
   }
 }
 
-pub fn run() {
-  let input_lines = shared::load_input_lines("day7.txt").unwrap();
+pub struct Day7;
+
+impl Solution for Day7 {
+  type Input = Grid<Cell>;
+  type Output1 = usize;
+  type Output2 = u64;
+
+  fn parse(input: &str) -> Result<Self::Input, AocError> {
+    parse_manifold(&shared::to_lines(input))
+  }
 
-  let result1 = puzzle1::solve(&input_lines);
-  println!("Day 7, Puzzle 1: {}", result1);
+  fn part1(input: &Self::Input) -> Result<Self::Output1, AocError> {
+    puzzle1::solve(input)
+  }
 
-  let result2 = puzzle2::solve(&input_lines);
-  println!("Day 7, Puzzle 2: {}", result2);
+  fn part2(input: &Self::Input) -> Result<Self::Output2, AocError> {
+    puzzle2::solve(input)
+  }
 }
 
 #[cfg(test)]
@@ -98,7 +398,7 @@ mod tests {
 
   #[test]
   fn test_solve_puzzle1() {
-    let result = puzzle1::solve(&TEST_INPUT);
+    let result = puzzle1::solve(&parse_manifold(&TEST_INPUT).unwrap()).unwrap();
     assert_eq!(result, 21);
   }
 
@@ -114,8 +414,143 @@ mod tests {
     //   ".....^.^.^.....".to_string(),
     //   "...............".to_string(),
     // ];
-    let result = puzzle2::solve(&TEST_INPUT);
+    let result = puzzle2::solve(&parse_manifold(&TEST_INPUT).unwrap()).unwrap();
     // println!("Result: {:?}", result);
     assert_eq!(result, 40);
   }
+
+  #[test]
+  fn test_simulate() {
+    let grid = parse_manifold(&TEST_INPUT).unwrap();
+    let simulation = simulate(&grid).unwrap();
+    assert!(simulation.splitters_hit.contains(&Coord::new(7, 2)));
+    // The splitter at (11, 14) sits in a gap between beams.
+    assert!(!simulation.splitters_hit.contains(&Coord::new(11, 14)));
+    assert_eq!(simulation.columns(0).collect::<Vec<_>>(), vec![7]);
+    assert_eq!(simulation.columns(4).collect::<Vec<_>>(), vec![5, 7, 9]);
+    assert_eq!(simulation.rows[4][&7], 2);
+
+    let annotated = simulation.annotate(&grid);
+    let lines = annotated.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], ".......S.......");
+    assert_eq!(lines[1], ".......|.......");
+    assert_eq!(lines[2], "......|^|......");
+    assert_eq!(lines[15], "|.|.|.|.|.|||.|");
+  }
+
+  #[test]
+  fn test_beams_leaving_the_sides() {
+    let input = ["S.S", "...", "^.^", "..."];
+    let grid = parse_manifold(&input.map(String::from)).unwrap();
+    let simulation = simulate(&grid).unwrap();
+    assert_eq!(simulation.splitters_hit.len(), 2);
+    // One timeline leaves each side, and both inner halves meet in the middle.
+    assert_eq!(simulation.timelines(), 4);
+    assert_eq!(simulation.rows[3], Beams::from([(1, 2)]));
+    assert_eq!(simulation.annotate(&grid), "S.S\n|.|\n^|^\n.|.");
+  }
+
+  /// Counts timelines by following every path separately.
+  fn count_paths(grid: &Grid<Cell>, beam: Beam) -> u64 {
+    step(grid, beam)
+      .1
+      .into_iter()
+      .map(|step| match step {
+        Step::Beam(child) => count_paths(grid, child),
+        Step::Leaves | Step::Absorbed => 1,
+      })
+      .sum()
+  }
+
+  fn manifold(text: &str) -> Grid<Cell> {
+    parse_manifold(&shared::to_lines(text)).unwrap()
+  }
+
+  #[test]
+  fn test_cell_kinds() {
+    // The mirrors send the beam left along row 1 and back down onto the
+    // one-sided splitter.
+    let grid = manifold(
+      "...S.\n\
+       /../.\n\
+       >.#..\n\
+       .....",
+    );
+    let simulation = simulate(&grid).unwrap();
+    assert_eq!(simulation.splitters_hit, BTreeSet::from([Coord::new(0, 2)]));
+    assert_eq!((simulation.left, simulation.absorbed), (1, 0));
+    assert_eq!(simulation.annotate(&grid), "...S.\n/--/.\n>|#..\n.|...");
+
+    let grid = manifold(
+      "S.S.S\n\
+       ^.<.#\n\
+       .....",
+    );
+    let simulation = simulate(&grid).unwrap();
+    assert_eq!(simulation.splitters_hit.len(), 2);
+    // Two timelines from the first splitter, one from the one-sided one and
+    // one stopped by the absorber.
+    assert_eq!((simulation.left, simulation.absorbed), (3, 1));
+    assert_eq!(simulation.rows[2], Beams::from([(1, 2)]));
+
+    let splitters = Cell::TABLE
+      .iter()
+      .filter(|(_, cell)| cell.is_splitter())
+      .map(|(symbol, _)| *symbol)
+      .collect::<String>();
+    assert_eq!(splitters, "^<>");
+  }
+
+  #[test]
+  fn test_timelines_match_path_enumeration() {
+    let layouts: [&[&str]; 3] = [
+      &TEST_INPUT.iter().map(String::as_str).collect::<Vec<_>>(),
+      &[
+        "..S...S..",
+        "..^......",
+        ".^.^./.\\.",
+        ".........",
+        "..<.^.>..",
+        "#.......#",
+        ".^.^.^.^.",
+        ".........",
+      ],
+      &[
+        "S.......",
+        "^.......",
+        ".\\.....S",
+        "..\\..../",
+        "...\\../.",
+        "...^.^^.",
+        "........",
+      ],
+    ];
+    for lines in layouts {
+      let grid = parse_manifold(
+        &lines
+          .iter()
+          .map(|line| line.to_string())
+          .collect::<Vec<_>>(),
+      )
+      .unwrap();
+      let sources = grid
+        .coords()
+        .filter(|coord| grid[*coord] == Cell::Start)
+        .map(|coord| count_paths(&grid, (coord, Direction::Down)))
+        .sum::<u64>();
+      assert_eq!(simulate(&grid).unwrap().timelines(), sources, "{:?}", lines);
+    }
+  }
+
+  #[test]
+  fn test_loops_are_errors() {
+    // The one-sided splitter drops the beam into a loop of mirrors.
+    let grid = manifold(
+      "..S..\n\
+       ./>\\.\n\
+       .....\n\
+       .\\./.",
+    );
+    assert!(matches!(simulate(&grid), Err(AocError::Solve(_))));
+  }
 }
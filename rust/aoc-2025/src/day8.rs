@@ -1,219 +1,115 @@
-use std::collections::HashMap;
-
+use crate::error::AocError;
 use crate::shared;
+use crate::solution::Solution;
+use network::{Linkage, Network};
+use spatial::Point;
 
-struct GraphManager<T> {
-  graphs: Vec<Graph<T>>,
-  lookup: HashMap<T, usize>,
-}
+pub mod metric;
+pub mod network;
+pub mod spatial;
 
-struct Graph<T> {
-  nodes: Vec<GraphNode<T>>,
-}
-impl<T> Graph<T> {
-  fn size(&self) -> usize {
-    self.nodes.len()
-  }
-}
+type Junction = Point<3>;
 
-#[derive(Debug, Clone, PartialEq)]
-struct GraphNode<T> {
-  value: T,
-  graph_id: usize,
-}
+/// Names for the first few axes in error messages.
+const AXES: [&str; 4] = ["x", "y", "z", "w"];
 
-impl<T> GraphManager<T>
-where
-  T: Eq + std::hash::Hash + Clone,
-{
-  fn new() -> Self {
-    Self {
-      graphs: Vec::new(),
-      lookup: HashMap::new(),
-    }
-  }
-
-  fn create_graph(&mut self) -> usize {
-    let id = self.graphs.len();
-    self.graphs.push(Graph { nodes: Vec::new() });
-    id
-  }
-
-  fn add_node(&mut self, graph_id: usize, value: T) {
-    let node = GraphNode { value, graph_id };
-    self.lookup.insert(node.value.clone(), graph_id);
-    self.graphs[graph_id].nodes.push(node);
-  }
-
-  fn merge_graphs(&mut self, target_id: usize, source_id: usize) {
-    if target_id == source_id {
-      return;
-    }
-    let source_nodes = std::mem::take(&mut self.graphs[source_id].nodes);
-    for mut node in source_nodes {
-      node.graph_id = target_id; // Update the graph reference
-      self.lookup.insert(node.value.clone(), target_id);
-      self.graphs[target_id].nodes.push(node);
-    }
-
-    self.graphs[source_id].nodes.clear();
-  }
-}
-
-type Junction = (i64, i64, i64);
-
-fn parse_input(input: &Vec<String>) -> Result<Vec<Junction>, String> {
-  input
-    .iter()
-    .map(|line| {
+/// Parses one point of `D` comma-separated coordinates per line.
+pub fn parse_points<const D: usize>(input: &[String]) -> Result<Vec<Point<D>>, AocError> {
+  (1..)
+    .zip(input)
+    .map(|(line_no, line)| {
       let parts = line.split(',').collect::<Vec<&str>>();
-      let x = parts[0]
-        .parse::<i64>()
-        .map_err(|e| format!("Failed to parse x: {}", e))?;
-      let y = parts[1]
-        .parse::<i64>()
-        .map_err(|e| format!("Failed to parse y: {}", e))?;
-      let z = parts[2]
-        .parse::<i64>()
-        .map_err(|e| format!("Failed to parse z: {}", e))?;
-      Ok((x, y, z))
+      if parts.len() != D {
+        return Err(AocError::parse(
+          line_no,
+          1,
+          format!(
+            "Expected {} comma-separated coordinates, got {}",
+            D,
+            parts.len()
+          ),
+        ));
+      }
+
+      let mut point = [0; D];
+      for (axis, part) in parts.iter().enumerate() {
+        point[axis] = part.trim().parse::<i64>().map_err(|e| {
+          let name = AXES.get(axis).map_or_else(
+            || format!("coordinate {}", axis + 1),
+            |name| name.to_string(),
+          );
+          AocError::parse(
+            line_no,
+            shared::column_of(line, part),
+            format!("Failed to parse {}: {}", name, e),
+          )
+        })?;
+      }
+      Ok(point)
     })
     .collect()
 }
 
-fn distance(a: &Junction, b: &Junction) -> f64 {
-  let (ax, ay, az) = *a;
-  let (bx, by, bz) = *b;
-  f64::sqrt(
-    (ax - bx).abs().pow(2) as f64 + (ay - by).abs().pow(2) as f64 + (az - bz).abs().pow(2) as f64,
-  )
-}
-
-fn closest_pairs(input: &Vec<Junction>) -> Vec<(&Junction, &Junction)> {
-  let mut pairs = input
-    .iter()
-    .flat_map(|a| {
-      input
-        .iter()
-        .filter(move |b| a != *b)
-        .map(move |b| (a, b, distance(a, b)))
-    })
-    .collect::<Vec<_>>();
-
-  pairs.sort_by(|(_, _, a_distance), (_, _, b_distance)| a_distance.total_cmp(b_distance));
-
-  let pairs_with_dupes = pairs.iter().map(|(a, b, _)| (*a, *b));
-
-  pairs_with_dupes
-    .zip(0..)
-    .filter(|(_, idx)| idx % 2 == 0)
-    .map(|(pair, _)| pair)
-    .collect::<Vec<(&Junction, &Junction)>>()
+fn parse_input(input: &[String]) -> Result<Vec<Junction>, AocError> {
+  parse_points(input)
 }
 
 mod puzzle1 {
   use super::*;
 
-  pub fn solve(input: &Vec<Junction>, n_connections: usize) -> usize {
-    let mut manager = GraphManager::<Junction>::new();
-
-    let pairs = closest_pairs(input);
-
-    for pair in pairs.iter().take(n_connections) {
-      let (a, b) = pair;
-
-      let a_graph_id = manager.lookup.get(a);
-      let b_graph_id = manager.lookup.get(b);
-
-      match (a_graph_id, b_graph_id) {
-        (Some(a_graph), Some(b_graph)) => manager.merge_graphs(*a_graph, *b_graph),
-        (Some(a_graph), None) => manager.add_node(*a_graph, **b),
-        (None, Some(b_graph)) => manager.add_node(*b_graph, **a),
-        (None, None) => {
-          let new_graph_id = manager.create_graph();
-          manager.add_node(new_graph_id, **a);
-          manager.add_node(new_graph_id, **b);
-        }
-      };
+  pub fn solve(input: &[Junction], n_connections: usize) -> Result<usize, AocError> {
+    let sizes = Network::new(input)
+      .clusters(Linkage::ClosestPairs(n_connections))
+      .sizes();
+    if sizes.len() < 3 {
+      return Err(AocError::solve(format!(
+        "Expected at least 3 circuits after {} connections, got {}",
+        n_connections,
+        sizes.len()
+      )));
     }
 
-    let mut sorted_graphs = manager
-      .graphs
-      .iter()
-      .clone()
-      .collect::<Vec<&Graph<Junction>>>();
-
-    sorted_graphs.sort_by(|a, b| b.size().cmp(&a.size()));
-    let three_largest_graphs = sorted_graphs[0..3].to_vec();
-    let size = three_largest_graphs
-      .iter()
-      .map(|graph| graph.size())
-      .product::<usize>();
-
-    size
+    Ok(sizes[..3].iter().product())
   }
 }
 
 mod puzzle2 {
-  use std::collections::VecDeque;
-
   use super::*;
 
-  pub fn solve(input: &Vec<Junction>) -> Result<i64, String> {
-    let mut manager = GraphManager::<Junction>::new();
-
-    let mut pairs = VecDeque::from(closest_pairs(input));
-
-    let mut last_pair = None;
-    while last_pair == None
-      || manager
-        .graphs
-        .iter()
-        .any(|graph| 0 < graph.size() && graph.size() < input.len())
-    {
-      let (a, b) = match pairs.pop_front() {
-        Some(pair) => pair,
-        None => return Err("No more pairs to process".to_string()),
-      };
-
-      last_pair = Some((a, b));
-
-      let a_graph_id = manager.lookup.get(a);
-      let b_graph_id = manager.lookup.get(b);
-
-      match (a_graph_id, b_graph_id) {
-        (Some(a_graph), Some(b_graph)) => manager.merge_graphs(*a_graph, *b_graph),
-        (Some(a_graph), None) => manager.add_node(*a_graph, *b),
-        (None, Some(b_graph)) => manager.add_node(*b_graph, *a),
-        (None, None) => {
-          let new_graph_id = manager.create_graph();
-          manager.add_node(new_graph_id, *a);
-          manager.add_node(new_graph_id, *b);
-        }
-      };
-    }
-
-    match last_pair {
-      Some(((ax, _, _), (bx, _, _))) => Ok(*ax * *bx),
-      None => Err("No pairs were processed".to_string()),
+  pub fn solve(input: &[Junction]) -> Result<i64, AocError> {
+    match Network::new(input).minimum_spanning_tree().last_edge() {
+      Some(edge) => Ok(input[edge.a][0] * input[edge.b][0]),
+      None => Err(AocError::solve("No pairs were processed")),
     }
   }
 }
 
-pub fn run() {
-  let input = shared::load_input_lines("day8.txt").unwrap();
-  let parsed_input = parse_input(&input).unwrap();
+pub struct Day8;
+
+impl Solution for Day8 {
+  type Input = Vec<Junction>;
+  type Output1 = usize;
+  type Output2 = i64;
 
-  let result1 = puzzle1::solve(&parsed_input, 1000);
-  println!("Day 8, Puzzle 1: {}", result1);
+  fn parse(input: &str) -> Result<Self::Input, AocError> {
+    parse_input(&shared::to_lines(input))
+  }
+
+  fn part1(input: &Self::Input) -> Result<Self::Output1, AocError> {
+    puzzle1::solve(input, 1000)
+  }
 
-  let result2 = puzzle2::solve(&parsed_input).unwrap();
-  println!("Day 8, Puzzle 2: {}", result2);
+  fn part2(input: &Self::Input) -> Result<Self::Output2, AocError> {
+    puzzle2::solve(input)
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::shared::disjoint_set::DisjointSet;
+  use metric::{Chebyshev, Euclidean, Manhattan, Metric};
+  use spatial::Pair;
   use std::sync::LazyLock;
   static TEST_INPUT: LazyLock<Vec<String>> = LazyLock::new(|| {
     vec![
@@ -243,18 +139,73 @@ mod tests {
   static PARSED_INPUT: LazyLock<Vec<Junction>> =
     LazyLock::new(|| parse_input(&TEST_INPUT).unwrap());
 
+  #[test]
+  fn test_parse_errors() {
+    let input = vec!["1,2,3".to_string(), "4,x,6".to_string()];
+    assert!(matches!(
+      parse_input(&input),
+      Err(AocError::Parse {
+        line: 2,
+        column: 3,
+        ..
+      })
+    ));
+    let input = vec!["1,2".to_string()];
+    assert!(matches!(
+      parse_input(&input),
+      Err(AocError::Parse { line: 1, .. })
+    ));
+  }
+
+  #[test]
+  fn test_parse_points() {
+    let input = vec!["1,2".to_string(), "-3, 4".to_string()];
+    assert_eq!(parse_points::<2>(&input).unwrap(), vec![[1, 2], [-3, 4]]);
+
+    let input = vec!["1,2,3,4".to_string(), "5,6,7,w".to_string()];
+    assert!(matches!(
+      parse_points::<4>(&input),
+      Err(AocError::Parse {
+        line: 2,
+        column: 7,
+        message,
+      }) if message.starts_with("Failed to parse w")
+    ));
+  }
+
+  /// Kruskal's algorithm over every pair, sorted up front.
+  fn brute_force_tree(metric: impl Metric) -> Vec<Pair> {
+    let mut circuits = (0..PARSED_INPUT.len()).collect::<DisjointSet<_>>();
+    spatial::all_pairs(&PARSED_INPUT, metric)
+      .into_iter()
+      .filter(|pair| circuits.union(&pair.a, &pair.b))
+      .collect()
+  }
+
+  #[test]
+  fn test_other_metrics() {
+    let tree = Network::with_metric(&PARSED_INPUT, Manhattan).minimum_spanning_tree();
+    assert_eq!(tree.edges(), brute_force_tree(Manhattan));
+
+    let tree = Network::with_metric(&PARSED_INPUT, Chebyshev).minimum_spanning_tree();
+    assert_eq!(tree.edges(), brute_force_tree(Chebyshev));
+
+    let tree = Network::new(&PARSED_INPUT).minimum_spanning_tree();
+    assert_eq!(tree.edges(), brute_force_tree(Euclidean));
+  }
+
   #[test]
   fn test_closest_pairs() {
-    let result = closest_pairs(&PARSED_INPUT)
-      .iter()
+    let result = Network::new(&PARSED_INPUT)
+      .pairs()
       .take(2)
-      .map(|(a, b)| ((**a).clone(), (**b).clone()))
+      .map(|pair| (PARSED_INPUT[pair.a], PARSED_INPUT[pair.b]))
       .collect::<Vec<(Junction, Junction)>>();
     assert_eq!(
       result,
       vec![
-        ((162, 817, 812), (425, 690, 689)),
-        ((162, 817, 812), (431, 825, 988))
+        ([162, 817, 812], [425, 690, 689]),
+        ([162, 817, 812], [431, 825, 988])
       ]
     );
   }
@@ -265,7 +216,7 @@ mod tests {
 
     #[test]
     fn test_solve_puzzle1() {
-      let result = solve(&PARSED_INPUT, 10);
+      let result = solve(&PARSED_INPUT, 10).unwrap();
       assert_eq!(result, 40);
     }
   }
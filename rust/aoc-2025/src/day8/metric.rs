@@ -0,0 +1,82 @@
+use super::spatial::Point;
+
+/// A way of measuring how far apart two points are. Distances only need to
+/// sort the same way as the true distance, so Euclidean distance is kept
+/// squared to stay exact.
+pub trait Metric {
+  fn distance<const D: usize>(&self, a: &Point<D>, b: &Point<D>) -> u128;
+
+  /// The least distance between two points that are `gap` apart on a
+  /// single axis.
+  fn axis_bound(&self, gap: u128) -> u128;
+}
+
+/// Straight-line distance, squared. Saturates instead of overflowing,
+/// which only happens for coordinates near the limits of `i64`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Euclidean;
+
+/// The sum of the distances along each axis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Manhattan;
+
+/// The largest distance along any axis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Chebyshev;
+
+fn gaps<'p, const D: usize>(a: &'p Point<D>, b: &'p Point<D>) -> impl Iterator<Item = u128> + 'p {
+  a.iter().zip(b).map(|(a, b)| a.abs_diff(*b) as u128)
+}
+
+impl Metric for Euclidean {
+  fn distance<const D: usize>(&self, a: &Point<D>, b: &Point<D>) -> u128 {
+    gaps(a, b).fold(0, |total, gap| total.saturating_add(self.axis_bound(gap)))
+  }
+
+  fn axis_bound(&self, gap: u128) -> u128 {
+    gap.saturating_mul(gap)
+  }
+}
+
+impl Metric for Manhattan {
+  fn distance<const D: usize>(&self, a: &Point<D>, b: &Point<D>) -> u128 {
+    gaps(a, b).fold(0, u128::saturating_add)
+  }
+
+  fn axis_bound(&self, gap: u128) -> u128 {
+    gap
+  }
+}
+
+impl Metric for Chebyshev {
+  fn distance<const D: usize>(&self, a: &Point<D>, b: &Point<D>) -> u128 {
+    gaps(a, b).max().unwrap_or(0)
+  }
+
+  fn axis_bound(&self, gap: u128) -> u128 {
+    gap
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_distances() {
+    let (a, b) = ([0, 0, 0], [1, -2, 3]);
+    assert_eq!(Euclidean.distance(&a, &b), 14);
+    assert_eq!(Manhattan.distance(&a, &b), 6);
+    assert_eq!(Chebyshev.distance(&a, &b), 3);
+
+    let (a, b) = ([-5, 7, 1, 0], [-5, 7, 1, 0]);
+    assert_eq!(Euclidean.distance(&a, &b), 0);
+    assert_eq!(Manhattan.distance(&a, &b), 0);
+    assert_eq!(Chebyshev.distance(&a, &b), 0);
+
+    let (a, b) = ([i64::MIN], [i64::MAX]);
+    assert_eq!(Euclidean.distance(&a, &b), (u64::MAX as u128).pow(2));
+    assert_eq!(Manhattan.distance(&a, &b), u64::MAX as u128);
+    assert_eq!(Chebyshev.distance(&a, &b), u64::MAX as u128);
+  }
+}
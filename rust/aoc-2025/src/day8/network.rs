@@ -0,0 +1,243 @@
+use crate::shared::disjoint_set::DisjointSet;
+
+use super::metric::{Euclidean, Metric};
+use super::spatial::{ClosestPairs, KdTree, Pair, Point};
+
+/// When single-linkage clustering stops joining the closest pairs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linkage {
+  /// Once there are at most this many clusters.
+  Clusters(usize),
+  /// Before the first pair further apart than this distance, as measured by
+  /// the metric. `Euclidean` distances are squared, so `WithinDistance(10)`
+  /// joins points up to √10 apart.
+  WithinDistance(u128),
+  /// After this many pairs, whether or not they joined two clusters.
+  ClosestPairs(usize),
+}
+
+/// Points joined by their closest pairs, as in Kruskal's algorithm.
+pub struct Network<'a, const D: usize, M = Euclidean> {
+  tree: KdTree<'a, D, M>,
+  len: usize,
+}
+
+impl<'a, const D: usize> Network<'a, D> {
+  pub fn new(points: &'a [Point<D>]) -> Self {
+    Network::with_metric(points, Euclidean)
+  }
+}
+
+impl<'a, const D: usize, M: Metric> Network<'a, D, M> {
+  pub fn with_metric(points: &'a [Point<D>], metric: M) -> Self {
+    Network {
+      tree: KdTree::with_metric(points, metric),
+      len: points.len(),
+    }
+  }
+
+  /// Every pair of points, in `Pair` order.
+  pub fn pairs(&self) -> ClosestPairs<'_, 'a, D, M> {
+    ClosestPairs::new(&self.tree)
+  }
+
+  /// Joins pairs until `linkage` says to stop. Returns the clusters, and
+  /// the pairs that joined two of them, in order.
+  fn link(&self, linkage: Linkage) -> (DisjointSet<usize>, Vec<Pair>) {
+    let mut clusters = (0..self.len).collect::<DisjointSet<_>>();
+    let mut edges = vec![];
+    for (seen, pair) in self.pairs().enumerate() {
+      let done = match linkage {
+        Linkage::Clusters(count) => clusters.component_count() <= count,
+        Linkage::WithinDistance(distance) => pair.distance > distance,
+        Linkage::ClosestPairs(count) => seen == count,
+      };
+      if done {
+        break;
+      }
+      if clusters.union(&pair.a, &pair.b) {
+        edges.push(pair);
+      }
+    }
+    (clusters, edges)
+  }
+
+  /// The tree joining every point with the least total distance. Squaring
+  /// preserves order, so under `Euclidean` this is also the tree of least
+  /// total straight-line length.
+  pub fn minimum_spanning_tree(&self) -> SpanningTree {
+    let (_, edges) = self.link(Linkage::Clusters(1));
+    SpanningTree { edges }
+  }
+
+  pub fn clusters(&self, linkage: Linkage) -> Clustering {
+    let (mut set, _) = self.link(linkage);
+    let mut clusters = vec![vec![]; self.len];
+    for point in 0..self.len {
+      if let Some(root) = set.find(&point) {
+        clusters[root].push(point);
+      }
+    }
+    clusters.retain(|cluster| !cluster.is_empty());
+    clusters.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+    Clustering { clusters }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanningTree {
+  edges: Vec<Pair>,
+}
+
+impl SpanningTree {
+  /// The edges in the order they were added, shortest first.
+  pub fn edges(&self) -> &[Pair] {
+    &self.edges
+  }
+
+  /// The sum of the edges' distances, as measured by the metric. Under
+  /// `Euclidean` that is the sum of their squared lengths, not the length
+  /// of the tree.
+  pub fn total_weight(&self) -> u128 {
+    self
+      .edges
+      .iter()
+      .fold(0, |total, edge| total.saturating_add(edge.distance))
+  }
+
+  /// The edge that connected the last two components.
+  pub fn last_edge(&self) -> Option<Pair> {
+    self.edges.last().copied()
+  }
+}
+
+/// Point indices grouped into clusters, largest first, then by their
+/// lowest index. Each cluster lists its points in increasing order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clustering {
+  clusters: Vec<Vec<usize>>,
+}
+
+impl Clustering {
+  pub fn clusters(&self) -> &[Vec<usize>] {
+    &self.clusters
+  }
+
+  pub fn sizes(&self) -> Vec<usize> {
+    self.clusters.iter().map(Vec::len).collect()
+  }
+
+  pub fn len(&self) -> usize {
+    self.clusters.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.clusters.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::day8::metric::{Chebyshev, Manhattan};
+
+  /// Points on a line with gaps of 1, 2, 1, 5, 1 and 3.
+  const LINE: [Point<1>; 7] = [[0], [1], [3], [4], [9], [10], [13]];
+
+  /// Prim's algorithm, for comparison.
+  fn prim_weight<const D: usize>(points: &[Point<D>], metric: impl Metric) -> u128 {
+    let mut best = vec![u128::MAX; points.len()];
+    let mut done = vec![false; points.len()];
+    let mut total = 0;
+    if !points.is_empty() {
+      best[0] = 0;
+    }
+    for _ in 0..points.len() {
+      let next = (0..points.len())
+        .filter(|i| !done[*i])
+        .min_by_key(|i| best[*i])
+        .unwrap();
+      done[next] = true;
+      total += best[next];
+      for i in 0..points.len() {
+        best[i] = best[i].min(metric.distance(&points[next], &points[i]));
+      }
+    }
+    total
+  }
+
+  #[test]
+  fn test_minimum_spanning_tree() {
+    let network = Network::new(&LINE);
+    let tree = network.minimum_spanning_tree();
+    let edges = tree
+      .edges()
+      .iter()
+      .map(|edge| (edge.a, edge.b))
+      .collect::<Vec<_>>();
+    assert_eq!(edges, vec![(0, 1), (2, 3), (4, 5), (1, 2), (5, 6), (3, 4)]);
+    assert_eq!(tree.total_weight(), 1 + 1 + 1 + 4 + 9 + 25);
+    assert_eq!(tree.last_edge().map(|edge| (edge.a, edge.b)), Some((3, 4)));
+
+    let points = (0..60)
+      .map(|i: i64| [i * 37 % 23, i * i % 17, i * 11 % 29])
+      .collect::<Vec<_>>();
+    let tree = Network::new(&points).minimum_spanning_tree();
+    assert_eq!(tree.edges().len(), points.len() - 1);
+    assert_eq!(tree.total_weight(), prim_weight(&points, Euclidean));
+    let tree = Network::with_metric(&points, Manhattan).minimum_spanning_tree();
+    assert_eq!(tree.total_weight(), prim_weight(&points, Manhattan));
+
+    let points = (0..50)
+      .map(|i: i64| [i * 7 % 13, i * 5 % 11])
+      .collect::<Vec<_>>();
+    let tree = Network::with_metric(&points, Chebyshev).minimum_spanning_tree();
+    assert_eq!(tree.total_weight(), prim_weight(&points, Chebyshev));
+
+    let points = (0..50)
+      .map(|i: i64| [i % 3, i * 3 % 7, i * i % 11, i * 13 % 5])
+      .collect::<Vec<_>>();
+    let tree = Network::new(&points).minimum_spanning_tree();
+    assert_eq!(tree.total_weight(), prim_weight(&points, Euclidean));
+
+    let tree = Network::new(&LINE[..1]).minimum_spanning_tree();
+    assert_eq!(tree.last_edge(), None);
+  }
+
+  #[test]
+  fn test_clusters() {
+    let network = Network::new(&LINE);
+    let clusters = |linkage| network.clusters(linkage).clusters().to_vec();
+
+    assert_eq!(
+      clusters(Linkage::Clusters(2)),
+      vec![vec![0, 1, 2, 3], vec![4, 5, 6]]
+    );
+    assert_eq!(
+      clusters(Linkage::WithinDistance(1)),
+      vec![vec![0, 1], vec![2, 3], vec![4, 5], vec![6]]
+    );
+    assert_eq!(
+      clusters(Linkage::ClosestPairs(4)),
+      vec![vec![0, 1, 2, 3], vec![4, 5], vec![6]]
+    );
+    assert_eq!(network.clusters(Linkage::Clusters(1)).sizes(), vec![7]);
+    assert_eq!(network.clusters(Linkage::Clusters(10)).len(), 7);
+
+    // Under Chebyshev distance, a diagonal step is as short as a straight
+    // one.
+    let points = [[0, 0], [1, 0], [2, 1], [5, 5]];
+    assert_eq!(
+      Network::new(&points)
+        .clusters(Linkage::WithinDistance(1))
+        .clusters(),
+      [vec![0, 1], vec![2], vec![3]]
+    );
+    assert_eq!(
+      Network::with_metric(&points, Chebyshev)
+        .clusters(Linkage::WithinDistance(1))
+        .clusters(),
+      [vec![0, 1, 2], vec![3]]
+    );
+  }
+}
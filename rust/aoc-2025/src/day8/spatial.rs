@@ -0,0 +1,301 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use super::metric::{Euclidean, Metric};
+
+/// A point with `D` integer coordinates.
+pub type Point<const D: usize> = [i64; D];
+
+/// A k-d tree over a slice of points, stored implicitly: each range of
+/// `order` is split by its median point on the axis for its depth.
+pub struct KdTree<'a, const D: usize, M = Euclidean> {
+  points: &'a [Point<D>],
+  order: Vec<usize>,
+  metric: M,
+}
+
+impl<'a, const D: usize> KdTree<'a, D> {
+  pub fn new(points: &'a [Point<D>]) -> Self {
+    KdTree::with_metric(points, Euclidean)
+  }
+}
+
+impl<'a, const D: usize, M: Metric> KdTree<'a, D, M> {
+  pub fn with_metric(points: &'a [Point<D>], metric: M) -> Self {
+    assert!(D > 0, "points need at least one dimension");
+    let mut order = (0..points.len()).collect::<Vec<_>>();
+    build(points, &mut order, 0);
+    KdTree {
+      points,
+      order,
+      metric,
+    }
+  }
+
+  /// Every point by increasing distance from `query`, ties broken by index.
+  /// Each step only visits the parts of the tree that could hold the next
+  /// point.
+  pub fn nearest(&self, query: Point<D>) -> Nearest<'_, 'a, D, M> {
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((
+      0,
+      Entry::Node {
+        start: 0,
+        end: self.order.len(),
+        depth: 0,
+      },
+    )));
+    Nearest {
+      tree: self,
+      query,
+      heap,
+    }
+  }
+}
+
+fn build<const D: usize>(points: &[Point<D>], order: &mut [usize], depth: usize) {
+  if order.len() <= 1 {
+    return;
+  }
+
+  let axis = depth % D;
+  let mid = order.len() / 2;
+  order.select_nth_unstable_by_key(mid, |i| (points[*i][axis], *i));
+  let (left, right) = order.split_at_mut(mid);
+  build(points, left, depth + 1);
+  build(points, &mut right[1..], depth + 1);
+}
+
+/// Something waiting to be visited, after everything closer. Nodes sort
+/// before points at the same distance, so a point is only returned once
+/// every point at that distance with a lower index has been found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Entry {
+  /// The points in `order[start..end]`.
+  Node {
+    start: usize,
+    end: usize,
+    depth: usize,
+  },
+  Point(usize),
+}
+
+/// Points by increasing distance from a query, with their distance.
+pub struct Nearest<'t, 'a, const D: usize, M = Euclidean> {
+  tree: &'t KdTree<'a, D, M>,
+  query: Point<D>,
+  /// Entries keyed by their distance, or a lower bound on it for nodes.
+  heap: BinaryHeap<Reverse<(u128, Entry)>>,
+}
+
+impl<const D: usize, M: Metric> Iterator for Nearest<'_, '_, D, M> {
+  type Item = (u128, usize);
+
+  fn next(&mut self) -> Option<(u128, usize)> {
+    while let Some(Reverse((bound, entry))) = self.heap.pop() {
+      let (start, end, depth) = match entry {
+        Entry::Point(index) => return Some((bound, index)),
+        Entry::Node { start, end, .. } if start == end => continue,
+        Entry::Node { start, end, depth } => (start, end, depth),
+      };
+
+      let mid = start + (end - start) / 2;
+      let index = self.tree.order[mid];
+      let point = &self.tree.points[index];
+      self.heap.push(Reverse((
+        self.tree.metric.distance(&self.query, point),
+        Entry::Point(index),
+      )));
+
+      // Everything before the median is at most `split` on this axis, and
+      // everything after it at least `split`.
+      let axis = depth % D;
+      let (query, split) = (self.query[axis], point[axis]);
+      let plane = self.tree.metric.axis_bound(query.abs_diff(split) as u128);
+      let children = [
+        (start, mid, if query > split { plane } else { 0 }),
+        (mid + 1, end, if query < split { plane } else { 0 }),
+      ];
+      for (start, end, plane) in children {
+        self.heap.push(Reverse((
+          bound.max(plane),
+          Entry::Node {
+            start,
+            end,
+            depth: depth + 1,
+          },
+        )));
+      }
+    }
+
+    None
+  }
+}
+
+/// Two points by index, with `a < b`, and their distance under the metric,
+/// which is squared for `Euclidean`.
+///
+/// Pairs are ordered lexicographically by `(distance, a, b)`: closest first,
+/// and pairs at the same distance by the input position of their points.
+/// No two pairs compare equal, so the order never depends on how they were
+/// found or sorted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Pair {
+  pub distance: u128,
+  pub a: usize,
+  pub b: usize,
+}
+
+/// Every pair of points, found by measuring them all, in `Pair` order.
+#[cfg(test)]
+pub fn all_pairs<const D: usize>(points: &[Point<D>], metric: impl Metric) -> Vec<Pair> {
+  let mut pairs = (0..points.len())
+    .flat_map(|a| (a + 1..points.len()).map(move |b| (a, b)))
+    .map(|(a, b)| Pair {
+      a,
+      b,
+      distance: metric.distance(&points[a], &points[b]),
+    })
+    .collect::<Vec<_>>();
+  pairs.sort_unstable();
+  pairs
+}
+
+/// Every unordered pair of points, lazily, in `Pair` order.
+pub struct ClosestPairs<'t, 'a, const D: usize, M = Euclidean> {
+  /// For each point, its neighbours with a higher index, so each pair comes
+  /// from exactly one of them.
+  neighbours: Vec<Nearest<'t, 'a, D, M>>,
+  /// The next pair from each point.
+  heap: BinaryHeap<Reverse<Pair>>,
+}
+
+impl<'t, 'a, const D: usize, M: Metric> ClosestPairs<'t, 'a, D, M> {
+  pub fn new(tree: &'t KdTree<'a, D, M>) -> Self {
+    let mut pairs = ClosestPairs {
+      neighbours: tree
+        .points
+        .iter()
+        .map(|point| tree.nearest(*point))
+        .collect(),
+      heap: BinaryHeap::new(),
+    };
+    for a in 0..tree.points.len() {
+      pairs.advance(a);
+    }
+    pairs
+  }
+
+  /// Queues the next pair from point `a`.
+  fn advance(&mut self, a: usize) {
+    if let Some((distance, b)) = self.neighbours[a].find(|(_, b)| *b > a) {
+      self.heap.push(Reverse(Pair { distance, a, b }));
+    }
+  }
+}
+
+impl<const D: usize, M: Metric> Iterator for ClosestPairs<'_, '_, D, M> {
+  type Item = Pair;
+
+  fn next(&mut self) -> Option<Pair> {
+    let Reverse(pair) = self.heap.pop()?;
+    self.advance(pair.a);
+    Some(pair)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::day8::metric::{Chebyshev, Manhattan};
+  use crate::shared::random::Lcg;
+
+  /// Pseudo-random points in a small cube, so there are plenty of ties.
+  fn random_points<const D: usize>(n: usize, size: i64) -> Vec<Point<D>> {
+    let mut random = Lcg::new(42).map(|value| value as i64 % size - size / 2);
+    (0..n)
+      .map(|_| std::array::from_fn(|_| random.next().unwrap()))
+      .collect()
+  }
+
+  fn check_nearest<const D: usize>(
+    points: &[Point<D>],
+    query: Point<D>,
+    metric: impl Metric + Copy,
+  ) {
+    let mut expected = (0..points.len())
+      .map(|i| (metric.distance(&query, &points[i]), i))
+      .collect::<Vec<_>>();
+    expected.sort();
+    let tree = KdTree::with_metric(points, metric);
+    assert_eq!(tree.nearest(query).collect::<Vec<_>>(), expected);
+  }
+
+  fn check_pairs<const D: usize>(points: &[Point<D>], metric: impl Metric + Copy) {
+    let tree = KdTree::with_metric(points, metric);
+    assert_eq!(
+      ClosestPairs::new(&tree).collect::<Vec<_>>(),
+      all_pairs(points, metric)
+    );
+  }
+
+  #[test]
+  fn test_nearest() {
+    let points = random_points::<3>(200, 40);
+    check_nearest(&points, [3, -2, 7], Euclidean);
+    check_nearest(&points, [3, -2, 7], Manhattan);
+    check_nearest(&points, [3, -2, 7], Chebyshev);
+
+    let points = random_points::<4>(200, 20);
+    check_nearest(&points, [0, 5, -9, 1], Euclidean);
+    check_nearest(&points, [0, 5, -9, 1], Manhattan);
+  }
+
+  #[test]
+  fn test_closest_pairs() {
+    let points = random_points::<3>(150, 30);
+    check_pairs(&points, Euclidean);
+    check_pairs(&points, Manhattan);
+    check_pairs(&points, Chebyshev);
+
+    let points = random_points::<2>(100, 10);
+    check_pairs(&points, Euclidean);
+    check_pairs(&points, Chebyshev);
+
+    let points = random_points::<4>(100, 12);
+    check_pairs(&points, Euclidean);
+    check_pairs(&points, Manhattan);
+
+    let tree = KdTree::<3>::new(&[]);
+    assert_eq!(ClosestPairs::new(&tree).next(), None);
+  }
+
+  #[test]
+  fn test_ties() {
+    // Every side of the square is the same length, as are both diagonals.
+    let points = [[0, 10], [10, 10], [0, 0], [10, 0]];
+    let tree = KdTree::new(&points);
+    let pairs = ClosestPairs::new(&tree)
+      .map(|pair| (pair.distance, pair.a, pair.b))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      pairs,
+      vec![
+        (100, 0, 1),
+        (100, 0, 2),
+        (100, 1, 3),
+        (100, 2, 3),
+        (200, 0, 3),
+        (200, 1, 2)
+      ]
+    );
+
+    // Duplicate points are distinct junctions at distance zero.
+    let points = [[5], [5], [5]];
+    let tree = KdTree::new(&points);
+    let pairs = ClosestPairs::new(&tree)
+      .map(|pair| (pair.a, pair.b))
+      .collect::<Vec<_>>();
+    assert_eq!(pairs, vec![(0, 1), (0, 2), (1, 2)]);
+  }
+}
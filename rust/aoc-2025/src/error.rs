@@ -0,0 +1,74 @@
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum AocError {
+  Io(io::Error),
+  /// Malformed puzzle input. `line` and `column` are 1-based.
+  Parse {
+    line: usize,
+    column: usize,
+    message: String,
+  },
+  /// The input parsed, but the solver could not produce an answer from it.
+  Solve(String),
+}
+
+impl AocError {
+  pub fn parse(line: usize, column: usize, message: impl Into<String>) -> Self {
+    AocError::Parse {
+      line,
+      column,
+      message: message.into(),
+    }
+  }
+
+  pub fn solve(message: impl Into<String>) -> Self {
+    AocError::Solve(message.into())
+  }
+}
+
+impl fmt::Display for AocError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      AocError::Io(e) => write!(f, "{}", e),
+      AocError::Parse {
+        line,
+        column,
+        message,
+      } => write!(f, "line {}, column {}: {}", line, column, message),
+      AocError::Solve(message) => write!(f, "{}", message),
+    }
+  }
+}
+
+impl std::error::Error for AocError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      AocError::Io(e) => Some(e),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for AocError {
+  fn from(e: io::Error) -> Self {
+    AocError::Io(e)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_display() {
+    assert_eq!(
+      AocError::parse(3, 7, "bad digit").to_string(),
+      "line 3, column 7: bad digit"
+    );
+    assert_eq!(AocError::solve("no pairs").to_string(), "no pairs");
+    let io_error = io::Error::new(io::ErrorKind::NotFound, "day9.txt: missing");
+    assert_eq!(AocError::from(io_error).to_string(), "day9.txt: missing");
+  }
+}
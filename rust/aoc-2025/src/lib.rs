@@ -0,0 +1,16 @@
+pub mod answers;
+pub mod cli;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod error;
+pub mod output;
+pub mod runner;
+pub mod shared;
+pub mod solution;
+pub mod timing;
@@ -1,28 +1,220 @@
-use std::io;
-use std::time::Instant;
-
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod shared;
-
-fn main() -> io::Result<()> {
-  day1::run()?;
-  //   day2::run(); // takes too long
-  let _ = day2::run; // get rid of warnings of unused code
-  day3::run();
-  day4::run()?;
-  day5::run()?;
-  day6::run();
-  day7::run();
-  let start = Instant::now();
-  day8::run();
-  let duration = start.elapsed();
-  println!("day8::run() took: {:?}", duration);
-  Ok(())
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+use aoc_2025::answers::{self, Answers, CheckOutcome};
+use aoc_2025::cli::{self, InputSource};
+use aoc_2025::error::AocError;
+use aoc_2025::output::{self, CheckSummary, Format};
+use aoc_2025::runner::{self, DayReport};
+use aoc_2025::shared;
+use aoc_2025::solution::{DAYS, Day, Options};
+use aoc_2025::timing::Timing;
+
+fn load_input(day: &Day, args: &cli::Args) -> io::Result<String> {
+  match &args.input {
+    InputSource::Default => {
+      let input_dir = shared::resolve_input_dir(args.input_dir.as_deref())?;
+      shared::load_input_str(&input_dir, &format!("day{}.txt", day.number))
+    }
+    InputSource::File(path) => shared::load_file_str(path),
+    InputSource::Stdin => {
+      let mut buf = String::new();
+      io::stdin().read_to_string(&mut buf)?;
+      Ok(buf)
+    }
+  }
+}
+
+fn load_answers(args: &cli::Args) -> Result<Answers, AocError> {
+  let path = match &args.answers {
+    Some(path) => path.clone(),
+    None => shared::resolve_input_dir(args.input_dir.as_deref())?.join(answers::ANSWERS_FILE),
+  };
+  Answers::load(&path)
+}
+
+/// The timing of every stage of a day that ran successfully.
+fn bench_stages(report: &DayReport) -> impl Iterator<Item = (String, &Timing)> {
+  let parse = report
+    .parse
+    .as_ref()
+    .ok()
+    .map(|timing| ("parse".to_string(), timing));
+  let parts = report
+    .parts
+    .iter()
+    .filter(|part| part.answer.is_ok())
+    .map(|part| (format!("part{}", part.part), &part.timing));
+  parse.into_iter().chain(parts)
+}
+
+/// Tab-separated min/median/p95 durations, in nanoseconds, for every stage
+/// that ran.
+fn bench_table(reports: &[DayReport]) -> String {
+  let mut table = "day\tstage\titerations\tmin_ns\tmedian_ns\tp95_ns\n".to_string();
+  for report in reports {
+    for (stage, timing) in bench_stages(report) {
+      table += &format!(
+        "{}\t{}\t{}\t{}\t{}\t{}\n",
+        report.day,
+        stage,
+        timing.iterations(),
+        timing.min().as_nanos(),
+        timing.median().as_nanos(),
+        timing.p95().as_nanos()
+      );
+    }
+  }
+  table
+}
+
+fn main() -> ExitCode {
+  let args = match cli::parse_args(std::env::args().skip(1)) {
+    Ok(args) => args,
+    Err(e) => {
+      eprintln!("{}\n\n{}", e, cli::USAGE);
+      return ExitCode::from(2);
+    }
+  };
+
+  if args.help {
+    println!("{}", cli::USAGE);
+    return ExitCode::SUCCESS;
+  }
+
+  if let Some(unknown) = args
+    .days
+    .iter()
+    .flatten()
+    .find(|n| !DAYS.iter().any(|day| day.number == **n))
+  {
+    eprintln!("Day {} has not been solved yet", unknown);
+    return ExitCode::from(2);
+  }
+
+  let selected = DAYS.iter().filter(|day| {
+    args
+      .days
+      .as_ref()
+      .is_none_or(|days| days.contains(&day.number))
+  });
+
+  let answers = if args.check {
+    match load_answers(&args) {
+      Ok(answers) => Some(answers),
+      Err(e) => {
+        eprintln!("Failed to load answers: {}", e);
+        return ExitCode::FAILURE;
+      }
+    }
+  } else {
+    None
+  };
+
+  let options = Options {
+    precision: args.precision,
+  };
+  let iterations = args.bench.unwrap_or(1);
+  let mut failed = false;
+  let mut summary = CheckSummary::default();
+  let mut reports = vec![];
+  let mut records = vec![];
+  for day in selected {
+    let report = match load_input(day, &args) {
+      Ok(input) => runner::run_day(day, &input, &options, &args.parts, iterations),
+      Err(e) => DayReport::failed(day.number, AocError::from(e)),
+    };
+
+    match &report.parse {
+      Ok(timing) if args.time && args.format == Format::Text => {
+        println!("Day {} parsed in {:?}", report.day, timing.median())
+      }
+      Ok(_) => {}
+      Err(e) => {
+        failed = true;
+        match args.format {
+          Format::Text => eprintln!("Day {} failed: {}", report.day, e),
+          _ => records.push(output::failure_record(report.day, e)),
+        }
+      }
+    }
+
+    for part in &report.parts {
+      let check = match (&answers, &part.answer) {
+        (Some(answers), Ok(answer)) => Some(answers.check(report.day, part.part, answer)),
+        _ => None,
+      };
+
+      match &check {
+        Some(CheckOutcome::Pass) => summary.passed += 1,
+        Some(CheckOutcome::Fail { .. }) => summary.failed += 1,
+        Some(CheckOutcome::Missing) => summary.missing += 1,
+        None => {}
+      }
+
+      if part.answer.is_err() || matches!(check, Some(CheckOutcome::Fail { .. })) {
+        failed = true;
+      }
+
+      match (args.format, &part.answer) {
+        (Format::Text, Ok(_)) => println!(
+          "{}",
+          output::text_line(report.day, part, check.as_ref(), args.time)
+        ),
+        (Format::Text, Err(e)) => {
+          eprintln!("Day {} Puzzle {} failed: {}", report.day, part.part, e)
+        }
+        _ => records.push(output::part_record(report.day, part, check.as_ref())),
+      }
+    }
+
+    if args.format == Format::Ndjson {
+      for record in records.drain(..) {
+        println!("{}", record);
+      }
+    }
+
+    reports.push(report);
+  }
+
+  match args.format {
+    Format::Text => {
+      if args.bench.is_some() {
+        print!("{}", bench_table(&reports));
+      }
+      if args.check {
+        println!(
+          "Checked answers: {} passed, {} failed, {} missing",
+          summary.passed, summary.failed, summary.missing
+        );
+      }
+    }
+    Format::Json | Format::Ndjson => {
+      if args.bench.is_some() || args.check {
+        let bench = args.bench.map(|_| {
+          reports
+            .iter()
+            .flat_map(|report| {
+              bench_stages(report)
+                .map(|(stage, timing)| output::bench_record(report.day, &stage, timing))
+            })
+            .collect()
+        });
+        records.push(output::summary_record(
+          bench,
+          args.check.then_some(&summary),
+        ));
+      }
+      match args.format {
+        Format::Json => println!("{}", serde_json::Value::from(records)),
+        _ => records.iter().for_each(|record| println!("{}", record)),
+      }
+    }
+  }
+
+  if failed {
+    ExitCode::FAILURE
+  } else {
+    ExitCode::SUCCESS
+  }
 }
@@ -0,0 +1,191 @@
+use serde_json::{Value, json};
+use strum_macros::EnumString;
+
+use crate::answers::CheckOutcome;
+use crate::error::AocError;
+use crate::runner::PartReport;
+use crate::timing::Timing;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum Format {
+  /// One `Day N Puzzle P: answer` line per result.
+  Text,
+  /// A single JSON array holding one object per result.
+  Json,
+  /// One JSON object per line, written as each result is produced.
+  Ndjson,
+}
+
+fn check_status(check: &CheckOutcome) -> &'static str {
+  match check {
+    CheckOutcome::Pass => "pass",
+    CheckOutcome::Fail { .. } => "fail",
+    CheckOutcome::Missing => "missing",
+  }
+}
+
+/// The human-readable line for a part that produced an answer.
+pub fn text_line(day: u8, part: &PartReport, check: Option<&CheckOutcome>, time: bool) -> String {
+  let answer = part.answer.as_deref().unwrap_or_default();
+  let mut line = format!("Day {} Puzzle {}: {}", day, part.part, answer);
+  match check {
+    Some(CheckOutcome::Fail { expected }) => line += &format!(" [FAIL, expected {}]", expected),
+    Some(check) => line += &format!(" [{}]", check_status(check)),
+    None => {}
+  }
+  if time {
+    line += &format!(" ({:?})", part.timing.median());
+  }
+  line
+}
+
+pub fn part_record(day: u8, part: &PartReport, check: Option<&CheckOutcome>) -> Value {
+  let mut record = json!({
+    "day": day,
+    "part": part.part.number(),
+    "answer": part.answer.as_ref().ok(),
+    "duration_ns": part.timing.median().as_nanos() as u64,
+    "error": part.answer.as_ref().err().map(|e| e.to_string()),
+  });
+
+  if let Some(check) = check {
+    record["check"] = json!(check_status(check));
+    if let CheckOutcome::Fail { expected } = check {
+      record["expected"] = json!(expected);
+    }
+  }
+  record
+}
+
+/// Totals of `--check` outcomes across a run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CheckSummary {
+  pub passed: usize,
+  pub failed: usize,
+  pub missing: usize,
+}
+
+/// `--bench` durations, in nanoseconds, for one stage of a day.
+pub fn bench_record(day: u8, stage: &str, timing: &Timing) -> Value {
+  json!({
+    "day": day,
+    "stage": stage,
+    "iterations": timing.iterations(),
+    "min_ns": timing.min().as_nanos() as u64,
+    "median_ns": timing.median().as_nanos() as u64,
+    "p95_ns": timing.p95().as_nanos() as u64,
+  })
+}
+
+/// The record that ends a run, holding whichever of the `--bench` table and
+/// the `--check` totals were asked for.
+pub fn summary_record(bench: Option<Vec<Value>>, check: Option<&CheckSummary>) -> Value {
+  let mut summary = json!({});
+  if let Some(bench) = bench {
+    summary["bench"] = json!(bench);
+  }
+  if let Some(check) = check {
+    summary["check"] = json!({
+      "passed": check.passed,
+      "failed": check.failed,
+      "missing": check.missing,
+    });
+  }
+  json!({ "summary": summary })
+}
+
+/// A record for a day whose input could not be loaded or parsed, so no part
+/// ran.
+pub fn failure_record(day: u8, error: &AocError) -> Value {
+  json!({
+    "day": day,
+    "part": null,
+    "answer": null,
+    "duration_ns": null,
+    "error": error.to_string(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::shared::Part;
+  use std::time::Duration;
+
+  fn report(answer: Result<String, AocError>) -> PartReport {
+    PartReport {
+      part: Part::Two,
+      answer,
+      timing: Timing::from_samples(vec![Duration::from_nanos(1500)]),
+    }
+  }
+
+  #[test]
+  fn test_parse_format() {
+    assert_eq!("text".parse::<Format>().unwrap(), Format::Text);
+    assert_eq!("ndjson".parse::<Format>().unwrap(), Format::Ndjson);
+    assert!("yaml".parse::<Format>().is_err());
+  }
+
+  #[test]
+  fn test_text_line() {
+    let part = report(Ok("42".to_string()));
+    assert_eq!(text_line(3, &part, None, false), "Day 3 Puzzle 2: 42");
+    assert_eq!(
+      text_line(3, &part, Some(&CheckOutcome::Pass), true),
+      "Day 3 Puzzle 2: 42 [pass] (1.5µs)"
+    );
+    let fail = CheckOutcome::Fail {
+      expected: "41".to_string(),
+    };
+    assert_eq!(
+      text_line(3, &part, Some(&fail), false),
+      "Day 3 Puzzle 2: 42 [FAIL, expected 41]"
+    );
+  }
+
+  #[test]
+  fn test_records() {
+    let part = report(Ok("42".to_string()));
+    assert_eq!(
+      part_record(3, &part, None).to_string(),
+      r#"{"answer":"42","day":3,"duration_ns":1500,"error":null,"part":2}"#
+    );
+
+    let fail = CheckOutcome::Fail {
+      expected: "41".to_string(),
+    };
+    let record = part_record(3, &part, Some(&fail));
+    assert_eq!(record["check"], "fail");
+    assert_eq!(record["expected"], "41");
+
+    let part = report(Err(AocError::solve("no pairs")));
+    let record = part_record(8, &part, None);
+    assert_eq!(record["answer"], Value::Null);
+    assert_eq!(record["error"], "no pairs");
+
+    let record = failure_record(1, &AocError::parse(2, 1, "bad"));
+    assert_eq!(record["part"], Value::Null);
+    assert_eq!(record["error"], "line 2, column 1: bad");
+  }
+
+  #[test]
+  fn test_summary_record() {
+    let timing = Timing::from_samples(vec![Duration::from_nanos(300), Duration::from_nanos(100)]);
+    let bench = vec![bench_record(5, "part1", &timing)];
+    let check = CheckSummary {
+      passed: 3,
+      failed: 1,
+      missing: 0,
+    };
+    assert_eq!(
+      summary_record(Some(bench), Some(&check)).to_string(),
+      concat!(
+        r#"{"summary":{"bench":[{"day":5,"iterations":2,"median_ns":100,"min_ns":100,"#,
+        r#""p95_ns":300,"stage":"part1"}],"check":{"failed":1,"missing":0,"passed":3}}}"#
+      )
+    );
+    assert_eq!(summary_record(None, None).to_string(), r#"{"summary":{}}"#);
+  }
+}
@@ -0,0 +1,81 @@
+use crate::error::AocError;
+use crate::shared::Part;
+use crate::solution::{Day, Options};
+use crate::timing::{self, Timing};
+
+pub struct PartReport {
+  pub part: Part,
+  pub answer: Result<String, AocError>,
+  pub timing: Timing,
+}
+
+pub struct DayReport {
+  pub day: u8,
+  /// How long parsing took, or why the input could not be loaded or parsed.
+  pub parse: Result<Timing, AocError>,
+  pub parts: Vec<PartReport>,
+}
+
+impl DayReport {
+  pub fn failed(day: u8, error: AocError) -> Self {
+    DayReport {
+      day,
+      parse: Err(error),
+      parts: vec![],
+    }
+  }
+}
+
+/// Parses `input` with `options` and solves each of `parts`, timing every
+/// stage over `iterations` runs.
+pub fn run_day(
+  day: &Day,
+  input: &str,
+  options: &Options,
+  parts: &[Part],
+  iterations: usize,
+) -> DayReport {
+  let (parsed, parse_timing) = timing::measure(iterations, || (day.parse)(input, options));
+  let parsed = match parsed {
+    Ok(parsed) => parsed,
+    Err(e) => return DayReport::failed(day.number, e),
+  };
+
+  let parts = parts
+    .iter()
+    .map(|part| {
+      let (answer, timing) = timing::measure(iterations, || parsed.solve(*part));
+      PartReport {
+        part: *part,
+        answer,
+        timing,
+      }
+    })
+    .collect();
+
+  DayReport {
+    day: day.number,
+    parse: Ok(parse_timing),
+    parts,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::solution::DAYS;
+
+  #[test]
+  fn test_run_day() {
+    let options = Options::default();
+    let report = run_day(&DAYS[0], "L68\nL30\nR48", &options, &Part::ALL, 3);
+    assert_eq!(report.parse.unwrap().iterations(), 3);
+    assert_eq!(report.parts.len(), 2);
+    assert_eq!(report.parts[0].answer.as_ref().unwrap(), "1");
+    assert_eq!(report.parts[1].timing.iterations(), 3);
+
+    let report = run_day(&DAYS[0], "Q1", &options, &Part::ALL, 3);
+    assert!(report.parse.is_err());
+    assert!(report.parts.is_empty());
+  }
+}
@@ -1,19 +1,142 @@
 use std::fs::File;
 use std::io;
-use std::io::{BufRead, BufReader, Read};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
 
-pub fn load_input_lines(filename: &str) -> io::Result<Vec<String>> {
-  let file =
-    File::open("../../puzzleInput/".to_string() + filename).expect("Failed to open input file");
-  let reader = BufReader::new(file);
-  reader.lines().collect::<io::Result<Vec<String>>>()
+use strum_macros::{Display, EnumString};
+
+pub mod disjoint_set;
+pub mod grid;
+#[cfg(test)]
+pub mod random;
+pub mod range_set;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumString)]
+pub enum Part {
+  #[strum(serialize = "1")]
+  One,
+  #[strum(serialize = "2")]
+  Two,
+}
+
+impl Part {
+  pub const ALL: [Part; 2] = [Part::One, Part::Two];
+
+  pub fn number(self) -> u8 {
+    match self {
+      Part::One => 1,
+      Part::Two => 2,
+    }
+  }
+}
+
+pub const INPUT_DIR_ENV: &str = "AOC_INPUT_DIR";
+const INPUT_DIR_NAME: &str = "puzzleInput";
+
+/// Looks for a `puzzleInput` directory in `start` or any of its ancestors.
+fn find_input_dir(start: &Path) -> Option<PathBuf> {
+  start
+    .ancestors()
+    .map(|dir| dir.join(INPUT_DIR_NAME))
+    .find(|candidate| candidate.is_dir())
 }
 
-pub fn load_input_str(filename: &str) -> io::Result<String> {
-  let file =
-    File::open("../../puzzleInput/".to_string() + filename).expect("Failed to open input file");
+/// Resolves the puzzle input directory, preferring an explicit `--input-dir`,
+/// then the `AOC_INPUT_DIR` environment variable, and finally a `puzzleInput`
+/// directory found by walking up from the crate manifest.
+pub fn resolve_input_dir(flag: Option<&Path>) -> io::Result<PathBuf> {
+  if let Some(dir) = flag {
+    return Ok(dir.to_path_buf());
+  }
+
+  if let Some(dir) = std::env::var_os(INPUT_DIR_ENV) {
+    return Ok(PathBuf::from(dir));
+  }
+
+  let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+  find_input_dir(manifest_dir).ok_or_else(|| {
+    io::Error::new(
+      io::ErrorKind::NotFound,
+      format!(
+        "No {} directory found above {}; set {} or pass --input-dir",
+        INPUT_DIR_NAME,
+        manifest_dir.display(),
+        INPUT_DIR_ENV
+      ),
+    )
+  })
+}
+
+pub fn load_file_str(path: &Path) -> io::Result<String> {
+  let with_path = |e: io::Error| io::Error::new(e.kind(), format!("{}: {}", path.display(), e));
+  let file = File::open(path).map_err(with_path)?;
   let mut reader = BufReader::new(file);
   let mut buf = String::new();
-  reader.read_to_string(&mut buf)?;
+  reader.read_to_string(&mut buf).map_err(with_path)?;
   Ok(buf)
 }
+
+pub fn load_input_str(input_dir: &Path, filename: &str) -> io::Result<String> {
+  load_file_str(&input_dir.join(filename))
+}
+
+pub fn to_lines(input: &str) -> Vec<String> {
+  input.lines().map(|line| line.to_string()).collect()
+}
+
+/// The 1-based column at which `part` starts. `part` must be a subslice of
+/// `line`, e.g. one produced by `split` or `trim`.
+pub fn column_of(line: &str, part: &str) -> usize {
+  let offset = part.as_ptr() as usize - line.as_ptr() as usize;
+  line[..offset].chars().count() + 1
+}
+
+/// Splits `line` on whitespace, pairing each token with its 1-based column.
+pub fn tokens_with_columns(line: &str) -> impl Iterator<Item = (usize, &str)> {
+  line
+    .split_whitespace()
+    .map(move |token| (column_of(line, token), token))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::fs;
+
+  #[test]
+  fn test_find_input_dir() {
+    let root = std::env::temp_dir().join(format!("aoc-2025-find-input-{}", std::process::id()));
+    let nested = root.join("rust").join("aoc-2025");
+    fs::create_dir_all(&nested).unwrap();
+    fs::create_dir_all(root.join(INPUT_DIR_NAME)).unwrap();
+
+    assert_eq!(find_input_dir(&nested), Some(root.join(INPUT_DIR_NAME)));
+    assert_eq!(find_input_dir(&root), Some(root.join(INPUT_DIR_NAME)));
+
+    fs::remove_dir_all(&root).unwrap();
+  }
+
+  #[test]
+  fn test_tokens_with_columns() {
+    let tokens = tokens_with_columns("  12 +  ab").collect::<Vec<_>>();
+    assert_eq!(tokens, vec![(3, "12"), (6, "+"), (9, "ab")]);
+  }
+
+  #[test]
+  fn test_resolve_input_dir_prefers_flag() {
+    let dir = Path::new("some/where");
+    assert_eq!(resolve_input_dir(Some(dir)).unwrap(), dir.to_path_buf());
+  }
+
+  #[test]
+  fn test_missing_file_names_path() {
+    let dir = Path::new("definitely/not/a/real/dir");
+    let err = load_input_str(dir, "day1.txt").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    assert!(
+      err
+        .to_string()
+        .contains(&dir.join("day1.txt").display().to_string())
+    );
+  }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A union-find over distinct values, with path compression and union by
+/// size, so every operation runs in near-constant amortised time.
+#[derive(Debug, Clone)]
+pub struct DisjointSet<T> {
+  index: HashMap<T, usize>,
+  parent: Vec<usize>,
+  /// The size of each component, only meaningful at its root.
+  size: Vec<usize>,
+  components: usize,
+}
+
+impl<T: Eq + Hash> DisjointSet<T> {
+  pub fn new() -> Self {
+    DisjointSet {
+      index: HashMap::new(),
+      parent: Vec::new(),
+      size: Vec::new(),
+      components: 0,
+    }
+  }
+
+  /// Adds `value` in a component of its own. Returns false if it was
+  /// already present.
+  pub fn insert(&mut self, value: T) -> bool {
+    let next = self.parent.len();
+    if self.index.contains_key(&value) {
+      return false;
+    }
+    self.index.insert(value, next);
+    self.parent.push(next);
+    self.size.push(1);
+    self.components += 1;
+    true
+  }
+
+  pub fn len(&self) -> usize {
+    self.parent.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.parent.is_empty()
+  }
+
+  pub fn contains(&self, value: &T) -> bool {
+    self.index.contains_key(value)
+  }
+
+  /// The number of disjoint components.
+  pub fn component_count(&self) -> usize {
+    self.components
+  }
+
+  fn root(&mut self, mut node: usize) -> usize {
+    let mut root = node;
+    while self.parent[root] != root {
+      root = self.parent[root];
+    }
+    while self.parent[node] != root {
+      node = std::mem::replace(&mut self.parent[node], root);
+    }
+    root
+  }
+
+  /// An id for the component holding `value`, shared by everything in that
+  /// component until the next union.
+  pub fn find(&mut self, value: &T) -> Option<usize> {
+    let node = *self.index.get(value)?;
+    Some(self.root(node))
+  }
+
+  pub fn connected(&mut self, a: &T, b: &T) -> bool {
+    matches!((self.find(a), self.find(b)), (Some(a), Some(b)) if a == b)
+  }
+
+  /// Merges the components holding `a` and `b`. Returns false if they were
+  /// already the same component, or either value is missing.
+  pub fn union(&mut self, a: &T, b: &T) -> bool {
+    let (Some(a), Some(b)) = (self.find(a), self.find(b)) else {
+      return false;
+    };
+    if a == b {
+      return false;
+    }
+
+    let (large, small) = if self.size[a] < self.size[b] {
+      (b, a)
+    } else {
+      (a, b)
+    };
+    self.parent[small] = large;
+    self.size[large] += self.size[small];
+    self.components -= 1;
+    true
+  }
+
+  /// The size of the component holding `value`.
+  pub fn component_size(&mut self, value: &T) -> Option<usize> {
+    let root = self.find(value)?;
+    Some(self.size[root])
+  }
+
+  /// The size of every component, largest first.
+  pub fn component_sizes(&self) -> Vec<usize> {
+    let mut sizes = (0..self.parent.len())
+      .filter(|node| self.parent[*node] == *node)
+      .map(|root| self.size[root])
+      .collect::<Vec<_>>();
+    sizes.sort_by(|a, b| b.cmp(a));
+    sizes
+  }
+}
+
+impl<T: Eq + Hash> Default for DisjointSet<T> {
+  fn default() -> Self {
+    DisjointSet::new()
+  }
+}
+
+impl<T: Eq + Hash> FromIterator<T> for DisjointSet<T> {
+  fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+    let mut set = DisjointSet::new();
+    for value in values {
+      set.insert(value);
+    }
+    set
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_union_find() {
+    let mut set = "abcdef".chars().collect::<DisjointSet<_>>();
+    assert_eq!(set.len(), 6);
+    assert_eq!(set.component_count(), 6);
+    assert!(!set.insert('a'));
+
+    assert!(set.union(&'a', &'b'));
+    assert!(set.union(&'c', &'d'));
+    assert!(set.union(&'b', &'d'));
+    assert!(!set.union(&'a', &'c'));
+    assert!(!set.union(&'a', &'z'));
+
+    assert!(set.connected(&'a', &'d'));
+    assert!(!set.connected(&'a', &'e'));
+    assert_eq!(set.find(&'z'), None);
+    assert_eq!(set.component_count(), 3);
+    assert_eq!(set.component_size(&'c'), Some(4));
+    assert_eq!(set.component_size(&'f'), Some(1));
+    assert_eq!(set.component_sizes(), vec![4, 1, 1]);
+  }
+
+  #[test]
+  fn test_long_chain() {
+    let n = 10_000;
+    let mut set = (0..n).collect::<DisjointSet<_>>();
+    for i in 1..n {
+      assert!(set.union(&(i - 1), &i));
+    }
+    assert_eq!(set.component_count(), 1);
+    assert_eq!(set.component_size(&0), Some(n));
+    assert_eq!(set.find(&0), set.find(&(n - 1)));
+  }
+}
@@ -0,0 +1,272 @@
+use std::ops::{Index, IndexMut};
+
+use crate::error::AocError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Coord {
+  pub x: usize,
+  pub y: usize,
+}
+
+impl Coord {
+  pub const fn new(x: usize, y: usize) -> Self {
+    Coord { x, y }
+  }
+
+  /// The coordinate `(dx, dy)` away, unless that would be negative.
+  pub fn offset(self, dx: isize, dy: isize) -> Option<Coord> {
+    Some(Coord {
+      x: self.x.checked_add_signed(dx)?,
+      y: self.y.checked_add_signed(dy)?,
+    })
+  }
+}
+
+/// Offsets of the 4 orthogonal neighbours.
+pub const VON_NEUMANN: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// Offsets of the 8 surrounding neighbours, including diagonals.
+pub const MOORE: [(isize, isize); 8] = [
+  (-1, -1),
+  (0, -1),
+  (1, -1),
+  (-1, 0),
+  (1, 0),
+  (-1, 1),
+  (0, 1),
+  (1, 1),
+];
+
+/// A rectangular grid stored row-major in a single `Vec`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+  cells: Vec<T>,
+  width: usize,
+  height: usize,
+}
+
+impl<T> Grid<T> {
+  pub fn new(width: usize, height: usize, fill: T) -> Self
+  where
+    T: Clone,
+  {
+    Grid {
+      cells: vec![fill; width * height],
+      width,
+      height,
+    }
+  }
+
+  /// Parses one row per line, mapping each character with `cell`. Every line
+  /// must be as wide as the first, and `cell` returns `None` for characters
+  /// that aren't allowed.
+  pub fn parse<S: AsRef<str>>(
+    lines: &[S],
+    cell: impl Fn(char) -> Option<T>,
+  ) -> Result<Self, AocError> {
+    let mut width = None;
+    let mut cells = vec![];
+    for (line_no, line) in (1..).zip(lines) {
+      let line = line.as_ref();
+      let line_width = line.chars().count();
+      let w = *width.get_or_insert(line_width);
+      if w != line_width {
+        return Err(AocError::parse(
+          line_no,
+          w.min(line_width) + 1,
+          format!(
+            "Inconsistent line widths: expected {}, got {}",
+            w, line_width
+          ),
+        ));
+      }
+
+      for (column, char) in (1..).zip(line.chars()) {
+        let value = cell(char).ok_or_else(|| {
+          AocError::parse(line_no, column, format!("Unexpected character '{}'", char))
+        })?;
+        cells.push(value);
+      }
+    }
+
+    Ok(Grid {
+      cells,
+      width: width.unwrap_or(0),
+      height: lines.len(),
+    })
+  }
+
+  pub fn width(&self) -> usize {
+    self.width
+  }
+
+  pub fn height(&self) -> usize {
+    self.height
+  }
+
+  pub fn in_bounds(&self, coord: Coord) -> bool {
+    coord.x < self.width && coord.y < self.height
+  }
+
+  pub fn get(&self, coord: Coord) -> Option<&T> {
+    if self.in_bounds(coord) {
+      Some(&self.cells[coord.y * self.width + coord.x])
+    } else {
+      None
+    }
+  }
+
+  pub fn get_mut(&mut self, coord: Coord) -> Option<&mut T> {
+    if self.in_bounds(coord) {
+      Some(&mut self.cells[coord.y * self.width + coord.x])
+    } else {
+      None
+    }
+  }
+
+  /// Every coordinate, row by row.
+  pub fn coords(&self) -> impl Iterator<Item = Coord> + use<T> {
+    let width = self.width;
+    (0..self.height).flat_map(move |y| (0..width).map(move |x| Coord::new(x, y)))
+  }
+
+  /// The in-bounds coordinates at each of `offsets` from `coord`.
+  pub fn neighbours<'a>(
+    &self,
+    coord: Coord,
+    offsets: &'a [(isize, isize)],
+  ) -> impl Iterator<Item = Coord> + use<'a, T> {
+    let (width, height) = (self.width, self.height);
+    offsets
+      .iter()
+      .filter_map(move |(dx, dy)| coord.offset(*dx, *dy))
+      .filter(move |n| n.x < width && n.y < height)
+  }
+
+  /// The up to 4 orthogonal neighbours of `coord`.
+  pub fn neighbours4(&self, coord: Coord) -> impl Iterator<Item = Coord> + use<T> {
+    self.neighbours(coord, &VON_NEUMANN)
+  }
+
+  /// The up to 8 neighbours of `coord`, including diagonals.
+  pub fn neighbours8(&self, coord: Coord) -> impl Iterator<Item = Coord> + use<T> {
+    self.neighbours(coord, &MOORE)
+  }
+
+  pub fn row(&self, y: usize) -> &[T] {
+    &self.cells[y * self.width..(y + 1) * self.width]
+  }
+
+  pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+    // `max(1)` keeps `chunks` happy for zero-width grids, which have no cells.
+    self.cells.chunks(self.width.max(1))
+  }
+
+  pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+    self.cells.iter().skip(x).step_by(self.width.max(1))
+  }
+
+  pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+    Grid {
+      cells: self.cells.iter().map(f).collect(),
+      width: self.width,
+      height: self.height,
+    }
+  }
+
+  /// Renders the grid back to text, one line per row.
+  pub fn render(&self, cell: impl Fn(&T) -> char) -> String {
+    self
+      .rows()
+      .map(|row| row.iter().map(&cell).collect::<String>())
+      .collect::<Vec<_>>()
+      .join("\n")
+  }
+}
+
+impl<T> Index<Coord> for Grid<T> {
+  type Output = T;
+
+  fn index(&self, coord: Coord) -> &T {
+    self.get(coord).unwrap_or_else(|| {
+      panic!(
+        "{:?} is outside a {}x{} grid",
+        coord, self.width, self.height
+      )
+    })
+  }
+}
+
+impl<T> IndexMut<Coord> for Grid<T> {
+  fn index_mut(&mut self, coord: Coord) -> &mut T {
+    let (width, height) = (self.width, self.height);
+    self
+      .get_mut(coord)
+      .unwrap_or_else(|| panic!("{:?} is outside a {}x{} grid", coord, width, height))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn digits() -> Grid<u32> {
+    Grid::parse(&["123", "456"], |c| c.to_digit(10)).unwrap()
+  }
+
+  #[test]
+  fn test_parse_and_index() {
+    let grid = digits();
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert_eq!(grid[Coord::new(2, 1)], 6);
+    assert_eq!(grid.get(Coord::new(3, 0)), None);
+    assert_eq!(grid.row(1), &[4, 5, 6]);
+    assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), vec![2, 5]);
+    assert_eq!(grid.rows().count(), 2);
+
+    let mut grid = grid;
+    grid[Coord::new(0, 0)] = 9;
+    assert_eq!(
+      grid.render(|d| char::from_digit(*d, 10).unwrap()),
+      "923\n456"
+    );
+  }
+
+  #[test]
+  fn test_parse_errors() {
+    assert!(matches!(
+      Grid::parse(&["123", "4x6"], |c| c.to_digit(10)),
+      Err(AocError::Parse {
+        line: 2,
+        column: 2,
+        ..
+      })
+    ));
+    assert!(matches!(
+      Grid::parse(&["123", "45"], |c| c.to_digit(10)),
+      Err(AocError::Parse {
+        line: 2,
+        column: 3,
+        ..
+      })
+    ));
+
+    let empty = Grid::parse::<&str>(&[], |c| c.to_digit(10)).unwrap();
+    assert_eq!((empty.width(), empty.height()), (0, 0));
+    assert_eq!(empty.coords().count(), 0);
+  }
+
+  #[test]
+  fn test_neighbours() {
+    let grid = Grid::new(3, 3, ());
+    let corner = Coord::new(0, 0);
+    assert_eq!(
+      grid.neighbours4(corner).collect::<Vec<_>>(),
+      vec![Coord::new(1, 0), Coord::new(0, 1)]
+    );
+    assert_eq!(grid.neighbours8(corner).count(), 3);
+    assert_eq!(grid.neighbours8(Coord::new(1, 1)).count(), 8);
+    assert_eq!(grid.neighbours4(Coord::new(2, 2)).count(), 2);
+    assert_eq!(grid.coords().nth(4), Some(Coord::new(1, 1)));
+  }
+}
@@ -0,0 +1,21 @@
+/// A deterministic pseudo-random sequence for test fixtures, from Knuth's
+/// MMIX linear congruential generator. Yields the high 31 bits of each state.
+pub struct Lcg(u64);
+
+impl Lcg {
+  pub fn new(seed: u64) -> Self {
+    Lcg(seed)
+  }
+}
+
+impl Iterator for Lcg {
+  type Item = u64;
+
+  fn next(&mut self) -> Option<u64> {
+    self.0 = self
+      .0
+      .wrapping_mul(6364136223846793005)
+      .wrapping_add(1442695040888963407);
+    Some(self.0 >> 33)
+  }
+}
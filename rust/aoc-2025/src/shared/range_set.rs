@@ -0,0 +1,387 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::error::AocError;
+use crate::shared::column_of;
+
+/// An integer type a `RangeSet` can hold.
+pub trait Integer: Copy + Ord {
+  fn saturating_add_one(self) -> Self;
+  /// `self + 1`, which must not overflow.
+  fn add_one(self) -> Self;
+  /// `self - 1`, which must not overflow.
+  fn sub_one(self) -> Self;
+  /// How many integers `start..=end` holds.
+  fn span(start: Self, end: Self) -> u128;
+}
+
+macro_rules! impl_integer {
+  ($($t:ty),*) => {$(
+    impl Integer for $t {
+      fn saturating_add_one(self) -> Self {
+        self.saturating_add(1)
+      }
+
+      fn add_one(self) -> Self {
+        self + 1
+      }
+
+      fn sub_one(self) -> Self {
+        self - 1
+      }
+
+      fn span(start: Self, end: Self) -> u128 {
+        (end as i128 - start as i128 + 1) as u128
+      }
+    }
+  )*};
+}
+
+impl_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// A set of integers stored as sorted, disjoint, non-adjacent inclusive
+/// ranges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeSet<T = i64> {
+  ranges: Vec<(T, T)>,
+}
+
+impl<T: Integer> Default for RangeSet<T> {
+  fn default() -> Self {
+    RangeSet::new()
+  }
+}
+
+impl<T: Integer> RangeSet<T> {
+  pub fn new() -> Self {
+    RangeSet { ranges: vec![] }
+  }
+
+  /// Builds a set from inclusive ranges in any order, merging any that
+  /// overlap or touch. Ranges whose start is after their end are empty.
+  pub fn from_ranges(ranges: impl IntoIterator<Item = (T, T)>) -> Self {
+    let mut ranges = ranges
+      .into_iter()
+      .filter(|(start, end)| start <= end)
+      .collect::<Vec<_>>();
+    ranges.sort();
+
+    let mut merged: Vec<(T, T)> = vec![];
+    for (start, end) in ranges {
+      match merged.last_mut() {
+        Some(last) if start <= last.1.saturating_add_one() => last.1 = last.1.max(end),
+        _ => merged.push((start, end)),
+      }
+    }
+
+    RangeSet { ranges: merged }
+  }
+
+  pub fn ranges(&self) -> &[(T, T)] {
+    &self.ranges
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.ranges.is_empty()
+  }
+
+  /// Adds `start..=end`, merging it with any ranges it overlaps or touches.
+  pub fn insert(&mut self, start: T, end: T) {
+    if start > end {
+      return;
+    }
+
+    // Every range in `first..last` overlaps or touches the new one.
+    let first = self
+      .ranges
+      .partition_point(|(_, e)| e.saturating_add_one() < start);
+    let last = self
+      .ranges
+      .partition_point(|(s, _)| *s <= end.saturating_add_one());
+
+    let merged = match &self.ranges[first..last] {
+      [] => (start, end),
+      [(s, _), .., (_, e)] | [(s, e)] => (start.min(*s), end.max(*e)),
+    };
+    self.ranges.splice(first..last, [merged]);
+  }
+
+  /// Adds every range of `other` to this set.
+  pub fn merge(&mut self, other: &RangeSet<T>) {
+    *self = self.union(other);
+  }
+
+  /// Binary search for the range holding `value`.
+  pub fn contains(&self, value: T) -> bool {
+    let idx = self.ranges.partition_point(|(_, end)| *end < value);
+    self
+      .ranges
+      .get(idx)
+      .is_some_and(|(start, _)| *start <= value)
+  }
+
+  pub fn union(&self, other: &RangeSet<T>) -> RangeSet<T> {
+    RangeSet::from_ranges(self.ranges.iter().chain(&other.ranges).copied())
+  }
+
+  pub fn intersection(&self, other: &RangeSet<T>) -> RangeSet<T> {
+    let mut ranges = vec![];
+    let (mut i, mut j) = (0, 0);
+    while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+      let (start, end) = (a.0.max(b.0), a.1.min(b.1));
+      if start <= end {
+        ranges.push((start, end));
+      }
+
+      if a.1 < b.1 {
+        i += 1;
+      } else {
+        j += 1;
+      }
+    }
+
+    RangeSet { ranges }
+  }
+
+  pub fn difference(&self, other: &RangeSet<T>) -> RangeSet<T> {
+    let mut ranges = vec![];
+    let mut first_removed = 0;
+    for &(start, end) in &self.ranges {
+      // Ranges of `other` that end before this one starts can't affect it,
+      // nor anything later in `self`.
+      while other
+        .ranges
+        .get(first_removed)
+        .is_some_and(|(_, e)| *e < start)
+      {
+        first_removed += 1;
+      }
+
+      // The first value of this range that is neither kept nor removed yet.
+      let mut cursor = Some(start);
+      for &(rs, re) in &other.ranges[first_removed..] {
+        let Some(from) = cursor else { break };
+        if rs > end {
+          break;
+        }
+        if rs > from {
+          ranges.push((from, rs.sub_one()));
+        }
+        cursor = if re >= end { None } else { Some(re.add_one()) };
+      }
+
+      if let Some(from) = cursor {
+        ranges.push((from, end));
+      }
+    }
+
+    RangeSet { ranges }
+  }
+
+  /// How many integers the set holds.
+  pub fn total_length(&self) -> u128 {
+    self
+      .ranges
+      .iter()
+      .map(|(start, end)| T::span(*start, *end))
+      .sum()
+  }
+}
+
+impl<T: Integer> FromIterator<(T, T)> for RangeSet<T> {
+  fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+    RangeSet::from_ranges(iter)
+  }
+}
+
+/// Parses an inclusive range written as `start-end`, where `range` is a
+/// subslice of `line`, the `line_no`th line of the input. Either bound may be
+/// negative, as in `-5--3`. The start must not be after the end.
+pub fn parse_range<T>(line_no: usize, line: &str, range: &str) -> Result<(T, T), AocError>
+where
+  T: Integer + FromStr,
+  T::Err: Display,
+{
+  let range = range.trim();
+  // A `-` at the very start is the start's sign, not the separator.
+  let separator = range
+    .char_indices()
+    .skip(1)
+    .find(|(_, char)| *char == '-')
+    .map(|(index, _)| index);
+  let (start, end) = separator
+    .map(|index| (&range[..index], &range[index + 1..]))
+    .ok_or_else(|| {
+      AocError::parse(
+        line_no,
+        column_of(line, range),
+        format!("Invalid range format: '{}'", range),
+      )
+    })?;
+
+  let parse_bound = |bound: &str| {
+    let bound = bound.trim();
+    bound.parse::<T>().map_err(|e| {
+      AocError::parse(
+        line_no,
+        column_of(line, bound),
+        format!("Failed to parse range '{}': {}", range, e),
+      )
+    })
+  };
+
+  let (start, end) = (parse_bound(start)?, parse_bound(end)?);
+  if start > end {
+    return Err(AocError::parse(
+      line_no,
+      column_of(line, range),
+      format!("Range '{}' starts after it ends", range),
+    ));
+  }
+
+  Ok((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn set(ranges: &[(i64, i64)]) -> RangeSet<i64> {
+    RangeSet::from_ranges(ranges.iter().copied())
+  }
+
+  #[test]
+  fn test_from_ranges_merges() {
+    let merged = set(&[(16, 20), (3, 5), (12, 18), (10, 14), (6, 6), (30, 29)]);
+    assert_eq!(merged.ranges(), &[(3, 6), (10, 20)]);
+    assert_eq!(merged.total_length(), 15);
+  }
+
+  #[test]
+  fn test_insert() {
+    let mut ranges = set(&[(1, 2), (5, 6), (10, 12)]);
+    ranges.insert(3, 3);
+    assert_eq!(ranges.ranges(), &[(1, 3), (5, 6), (10, 12)]);
+    ranges.insert(4, 10);
+    assert_eq!(ranges.ranges(), &[(1, 12)]);
+    ranges.insert(20, 25);
+    ranges.insert(-5, -3);
+    assert_eq!(ranges.ranges(), &[(-5, -3), (1, 12), (20, 25)]);
+    ranges.insert(9, 8);
+    assert_eq!(ranges.ranges(), &[(-5, -3), (1, 12), (20, 25)]);
+  }
+
+  #[test]
+  fn test_contains() {
+    let ranges = set(&[(3, 5), (10, 14), (16, 20), (12, 18)]);
+    let fresh = [1, 5, 8, 11, 17, 32]
+      .into_iter()
+      .filter(|i| ranges.contains(*i))
+      .collect::<Vec<_>>();
+    assert_eq!(fresh, vec![5, 11, 17]);
+    assert!(!RangeSet::<i64>::new().contains(0));
+  }
+
+  #[test]
+  fn test_set_operations() {
+    let a = set(&[(1, 10), (20, 30)]);
+    let b = set(&[(5, 22), (28, 40)]);
+
+    assert_eq!(a.union(&b).ranges(), &[(1, 40)]);
+    assert_eq!(a.intersection(&b).ranges(), &[(5, 10), (20, 22), (28, 30)]);
+    assert_eq!(a.difference(&b).ranges(), &[(1, 4), (23, 27)]);
+    assert_eq!(b.difference(&a).ranges(), &[(11, 19), (31, 40)]);
+    assert_eq!(a.difference(&a), RangeSet::new());
+    assert_eq!(a.difference(&RangeSet::new()), a);
+
+    let mut merged = a.clone();
+    merged.merge(&b);
+    assert_eq!(merged, a.union(&b));
+  }
+
+  #[test]
+  fn test_extreme_bounds() {
+    let everything = set(&[(i64::MIN, i64::MAX)]);
+    assert_eq!(everything.total_length(), 1u128 << 64);
+    let without_ends = everything.difference(&set(&[(i64::MIN, 0), (i64::MAX, i64::MAX)]));
+    assert_eq!(without_ends.ranges(), &[(1, i64::MAX - 1)]);
+  }
+
+  #[test]
+  fn test_other_integer_types() {
+    let mut bytes = RangeSet::<u8>::from_ranges([(250, 255), (0, 3)]);
+    bytes.insert(4, 4);
+    assert_eq!(bytes.ranges(), &[(0, 4), (250, 255)]);
+    assert_eq!(bytes.total_length(), 11);
+    let middle = RangeSet::from_ranges([(u8::MIN, u8::MAX)]).difference(&bytes);
+    assert_eq!(middle.ranges(), &[(5, 249)]);
+
+    let indices = [(2usize, 4usize), (7, 9)]
+      .into_iter()
+      .collect::<RangeSet<_>>();
+    assert!(indices.contains(8));
+    assert!(!indices.contains(5));
+  }
+
+  #[test]
+  fn test_parse_range() {
+    let line = "11-22, 95-115";
+    assert_eq!(parse_range::<i64>(1, line, &line[..5]).unwrap(), (11, 22));
+    assert_eq!(parse_range::<u32>(1, line, &line[6..]).unwrap(), (95, 115));
+    assert!(matches!(
+      parse_range::<i64>(2, "9-x", "9-x"),
+      Err(AocError::Parse {
+        line: 2,
+        column: 3,
+        ..
+      })
+    ));
+    assert!(parse_range::<i64>(1, "95115", "95115").is_err());
+    assert!(parse_range::<u8>(1, "1-300", "1-300").is_err());
+  }
+
+  #[test]
+  fn test_parse_negative_range() {
+    let line = "-5--3, -2-4, 7 - -1";
+    assert_eq!(parse_range::<i64>(1, line, &line[..5]).unwrap(), (-5, -3));
+    assert_eq!(parse_range::<i8>(1, line, &line[6..11]).unwrap(), (-2, 4));
+    // `7 - -1` runs backwards.
+    assert!(matches!(
+      parse_range::<i64>(1, line, &line[12..]),
+      Err(AocError::Parse { column: 14, .. })
+    ));
+    assert!(matches!(
+      parse_range::<u32>(3, "-1-4", "-1-4"),
+      Err(AocError::Parse {
+        line: 3,
+        column: 1,
+        ..
+      })
+    ));
+    assert!(parse_range::<i64>(1, "-5", "-5").is_err());
+
+    let set = [(-5, -3), (-2, 4)].into_iter().collect::<RangeSet<i64>>();
+    assert!(set.contains(-4));
+    assert_eq!(set.total_length(), 10);
+  }
+
+  #[test]
+  fn test_parse_reversed_range() {
+    let line = "1-2, 5-3";
+    let error = parse_range::<i64>(4, line, &line[5..]).unwrap_err();
+    assert!(matches!(
+      error,
+      AocError::Parse {
+        line: 4,
+        column: 6,
+        ..
+      }
+    ));
+    assert!(
+      error
+        .to_string()
+        .contains("Range '5-3' starts after it ends")
+    );
+    assert_eq!(parse_range::<i64>(1, "7-7", "7-7").unwrap(), (7, 7));
+  }
+}
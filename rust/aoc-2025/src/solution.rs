@@ -0,0 +1,110 @@
+use std::fmt::Display;
+use std::marker::PhantomData;
+
+use crate::day6::Precision;
+use crate::error::AocError;
+use crate::shared::Part;
+use crate::{day1, day2, day3, day4, day5, day6, day7, day8};
+
+/// Command-line settings that change how some days solve their puzzles.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Options {
+  /// The integer width day 6 does its arithmetic in.
+  pub precision: Precision,
+}
+
+pub trait Solution {
+  type Input;
+  type Output1: Display;
+  type Output2: Display;
+
+  fn parse(input: &str) -> Result<Self::Input, AocError>;
+
+  /// `parse` for days that read `options`; the rest ignore them.
+  fn parse_with(input: &str, options: &Options) -> Result<Self::Input, AocError> {
+    let _ = options;
+    Self::parse(input)
+  }
+
+  fn part1(input: &Self::Input) -> Result<Self::Output1, AocError>;
+  fn part2(input: &Self::Input) -> Result<Self::Output2, AocError>;
+}
+
+/// A parsed puzzle input with its `Solution` type erased, so that days with
+/// different input and output types can sit side by side in `DAYS`.
+pub trait ParsedInput {
+  fn solve(&self, part: Part) -> Result<String, AocError>;
+}
+
+struct Parsed<S: Solution> {
+  input: S::Input,
+  solution: PhantomData<S>,
+}
+
+impl<S: Solution> ParsedInput for Parsed<S> {
+  fn solve(&self, part: Part) -> Result<String, AocError> {
+    match part {
+      Part::One => S::part1(&self.input).map(|answer| answer.to_string()),
+      Part::Two => S::part2(&self.input).map(|answer| answer.to_string()),
+    }
+  }
+}
+
+/// Parses a day's input into its type-erased form.
+pub type ParseFn = fn(&str, &Options) -> Result<Box<dyn ParsedInput>, AocError>;
+
+fn parse_erased<S: Solution + 'static>(
+  input: &str,
+  options: &Options,
+) -> Result<Box<dyn ParsedInput>, AocError> {
+  let input = S::parse_with(input, options)?;
+  Ok(Box::new(Parsed::<S> {
+    input,
+    solution: PhantomData,
+  }))
+}
+
+pub struct Day {
+  pub number: u8,
+  pub parse: ParseFn,
+}
+
+impl Day {
+  const fn new<S: Solution + 'static>(number: u8) -> Self {
+    Day {
+      number,
+      parse: parse_erased::<S>,
+    }
+  }
+}
+
+pub static DAYS: [Day; 8] = [
+  Day::new::<day1::Day1>(1),
+  Day::new::<day2::Day2>(2),
+  Day::new::<day3::Day3>(3),
+  Day::new::<day4::Day4>(4),
+  Day::new::<day5::Day5>(5),
+  Day::new::<day6::Day6>(6),
+  Day::new::<day7::Day7>(7),
+  Day::new::<day8::Day8>(8),
+];
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn days_are_unique_and_in_order() {
+    let numbers = DAYS.iter().map(|day| day.number).collect::<Vec<_>>();
+    assert_eq!(numbers, (1..=8).collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn registry_solves_both_parts() {
+    let day = &DAYS[0];
+    let input = "L68\nL30\nR48\nL5\nR60\nL55\nL1\nL99\nR14\nL82";
+    let parsed = (day.parse)(input, &Options::default()).unwrap();
+    assert_eq!(parsed.solve(Part::One).unwrap(), "3");
+    assert_eq!(parsed.solve(Part::Two).unwrap(), "6");
+  }
+}
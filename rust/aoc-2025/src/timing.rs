@@ -0,0 +1,91 @@
+use std::time::{Duration, Instant};
+
+/// Wall-clock samples for one stage (parse, part 1 or part 2) of a day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timing {
+  samples: Vec<Duration>,
+}
+
+impl Timing {
+  pub fn from_samples(mut samples: Vec<Duration>) -> Self {
+    samples.sort();
+    Timing { samples }
+  }
+
+  pub fn iterations(&self) -> usize {
+    self.samples.len()
+  }
+
+  pub fn min(&self) -> Duration {
+    self.samples.first().copied().unwrap_or_default()
+  }
+
+  pub fn median(&self) -> Duration {
+    self.percentile(50)
+  }
+
+  pub fn p95(&self) -> Duration {
+    self.percentile(95)
+  }
+
+  /// Nearest-rank percentile of the samples.
+  fn percentile(&self, pct: usize) -> Duration {
+    if self.samples.is_empty() {
+      return Duration::ZERO;
+    }
+    let rank = (pct * self.samples.len()).div_ceil(100).max(1);
+    self.samples[rank - 1]
+  }
+}
+
+/// Runs `f` `iterations` times (at least once), returning the last result
+/// along with the time each run took. Stops early if `f` fails, since every
+/// further run would fail the same way.
+pub fn measure<T, E>(
+  iterations: usize,
+  mut f: impl FnMut() -> Result<T, E>,
+) -> (Result<T, E>, Timing) {
+  let mut samples = vec![];
+  loop {
+    let start = Instant::now();
+    let result = f();
+    samples.push(start.elapsed());
+
+    if result.is_err() || samples.len() >= iterations {
+      return (result, Timing::from_samples(samples));
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_stats() {
+    let timing = Timing::from_samples((1..=20).rev().map(Duration::from_millis).collect());
+    assert_eq!(timing.iterations(), 20);
+    assert_eq!(timing.min(), Duration::from_millis(1));
+    assert_eq!(timing.median(), Duration::from_millis(10));
+    assert_eq!(timing.p95(), Duration::from_millis(19));
+
+    let single = Timing::from_samples(vec![Duration::from_millis(7)]);
+    assert_eq!(single.median(), Duration::from_millis(7));
+    assert_eq!(single.p95(), Duration::from_millis(7));
+  }
+
+  #[test]
+  fn test_measure() {
+    let mut calls = 0;
+    let (result, timing) = measure(5, || {
+      calls += 1;
+      Ok::<_, ()>(calls)
+    });
+    assert_eq!(result, Ok(5));
+    assert_eq!(timing.iterations(), 5);
+
+    let (result, timing) = measure(5, || Err::<(), _>("boom"));
+    assert_eq!(result, Err("boom"));
+    assert_eq!(timing.iterations(), 1);
+  }
+}